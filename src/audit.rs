@@ -0,0 +1,142 @@
+//! A record of every change made to a cluster from the app, kept in a JSON lines file that's only ever appended to
+
+use std::{
+    fmt::Display,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, kube_context::KubeContext, resource_type::ResourceType, state};
+
+const AUDIT_FILE: &str = "audit.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Delete,
+    /// The YAML of the object was replaced
+    Apply,
+    /// A deleted object was created again from a snapshot
+    Recreate,
+    Restart,
+    Scale,
+    /// The labels or annotations were changed
+    Label,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Delete => write!(f, "Delete"),
+            Self::Apply => write!(f, "Apply"),
+            Self::Recreate => write!(f, "Recreate"),
+            Self::Restart => write!(f, "Restart"),
+            Self::Scale => write!(f, "Scale"),
+            Self::Label => write!(f, "Label"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    Succeeded,
+    Failed { error: String },
+}
+
+/// One change that was made, or attempted, from the app
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub context: String,
+    pub namespace: String,
+    /// The user of the context in the kubeconfig
+    pub user: Option<String>,
+    /// Who the request was sent on behalf of, if the user was impersonating someone
+    #[serde(default)]
+    pub impersonating: Option<String>,
+    pub action: Action,
+    pub resource_type: ResourceType,
+    pub name: String,
+    pub outcome: Outcome,
+}
+
+impl AuditEntry {
+    pub fn new(
+        context: &KubeContext,
+        namespace: String,
+        action: Action,
+        resource_type: ResourceType,
+        name: String,
+        result: Result<(), &Error>,
+    ) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            context: context.get_name(),
+            namespace,
+            user: context.get_user(),
+            impersonating: context
+                .get_impersonation()
+                .map(|impersonation| impersonation.to_string()),
+            action,
+            resource_type,
+            name,
+            outcome: match result {
+                Ok(()) => Outcome::Succeeded,
+                Err(error) => Outcome::Failed {
+                    error: error.get_message(),
+                },
+            },
+        }
+    }
+}
+
+pub fn audit_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join(state::STATE_DIR).join(AUDIT_FILE))
+}
+
+/// Adds the entry to the end of the audit file.
+///
+/// This is done synchronously, like saving the state, so that entries are written in the order they happened.
+pub fn record(entry: &AuditEntry) -> Result<(), Error> {
+    let Some(path) = audit_file() else {
+        return Err(Error::new("Unable to find the config directory".into()));
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    // a single write of the whole line, so that two entries can't be interleaved
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Reads every entry in the audit file, oldest first.
+/// Lines that can't be parsed are skipped, so that one bad line doesn't hide the rest of the history.
+pub async fn read() -> Result<Vec<AuditEntry>, Error> {
+    let Some(path) = audit_file() else {
+        return Err(Error::new("Unable to find the config directory".into()));
+    };
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let contents = fs::read_to_string(&path)?;
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .inspect_err(|error| {
+                    tracing::warn!("Skipping invalid line in {}: {}", path.display(), error)
+                })
+                .ok()
+        })
+        .collect())
+}
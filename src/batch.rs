@@ -0,0 +1,104 @@
+//! Actions that are run on several objects at once
+
+use std::fmt::Display;
+
+use iced::futures::{stream, StreamExt};
+
+use crate::{
+    audit, cluster_object::ClusterObject, constants, error::Error, kube_context::KubeContext,
+    kube_interface, metadata::MetadataPatch, settings::DeletePropagation,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchAction {
+    Delete(DeletePropagation),
+    Restart,
+    Scale(i32),
+    /// Sets and removes labels and annotations
+    Label(MetadataPatch),
+}
+
+impl BatchAction {
+    pub fn audit_action(&self) -> audit::Action {
+        match self {
+            Self::Delete(..) => audit::Action::Delete,
+            Self::Restart => audit::Action::Restart,
+            Self::Scale(..) => audit::Action::Scale,
+            Self::Label(..) => audit::Action::Label,
+        }
+    }
+
+    async fn run_one(
+        &self,
+        context: KubeContext,
+        cluster_object: ClusterObject,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Delete(propagation_policy) => {
+                kube_interface::delete_without_snapshot(
+                    context,
+                    cluster_object,
+                    *propagation_policy,
+                )
+                .await
+            }
+            Self::Restart => kube_interface::restart(context, cluster_object).await,
+            Self::Scale(replicas) => {
+                kube_interface::scale(context, cluster_object, *replicas).await
+            }
+            Self::Label(patch) => {
+                kube_interface::patch_metadata(context, cluster_object, patch.clone()).await
+            }
+        }
+    }
+}
+
+impl Display for BatchAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Delete(..) => write!(f, "Delete"),
+            Self::Restart => write!(f, "Restart"),
+            Self::Scale(replicas) => write!(f, "Scale to {}", replicas),
+            Self::Label(..) => write!(f, "Edit labels and annotations"),
+        }
+    }
+}
+
+/// What happened to each object of a batch action
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    pub action: BatchAction,
+    pub results: Vec<(ClusterObject, Result<(), Error>)>,
+}
+
+impl BatchResult {
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|(_, res)| res.is_ok()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.succeeded()
+    }
+}
+
+/// Runs the action on every object, a few at a time so that the API server isn't flooded.
+/// One object failing doesn't stop the others.
+pub async fn run(
+    context: KubeContext,
+    cluster_objects: Vec<ClusterObject>,
+    action: BatchAction,
+) -> BatchResult {
+    let results = stream::iter(cluster_objects)
+        .map(|cluster_object| {
+            let context = context.clone();
+            let action = &action;
+            async move {
+                let res = action.run_one(context, cluster_object.clone()).await;
+                (cluster_object, res)
+            }
+        })
+        .buffer_unordered(constants::BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    BatchResult { action, results }
+}
@@ -1,30 +1,31 @@
-use iced::{
-    widget::button::{self},
-    Theme, Vector,
-};
+use iced::{widget::button, Theme};
 
-use crate::{colours, sizes};
+use crate::{colours::Palette, sizes};
 
 pub enum ButtonTheme {
     Primary,
     Secondary,
+    /// No background, for buttons that sit on top of a dark container
+    Ghost,
 }
 
 impl iced::widget::button::StyleSheet for ButtonTheme {
     type Style = Theme;
 
-    fn active(&self, _style: &Self::Style) -> button::Appearance {
+    fn active(&self, style: &Self::Style) -> button::Appearance {
+        let palette = Palette::of(style);
         let colour = match self {
-            ButtonTheme::Primary => colours::get_blue(),
-            ButtonTheme::Secondary => colours::get_lilac(),
+            ButtonTheme::Primary => Some(palette.accent),
+            ButtonTheme::Secondary => Some(palette.secondary_accent),
+            ButtonTheme::Ghost => None,
         };
 
         button::Appearance {
-            background: Some(colour.into()),
+            background: colour.map(Into::into),
             border_radius: sizes::BORDER_RADIUS.into(),
             text_color: match self {
-                ButtonTheme::Primary => colours::get_white().into(),
-                ButtonTheme::Secondary => colours::get_black().into(),
+                ButtonTheme::Primary | ButtonTheme::Ghost => palette.on_accent,
+                ButtonTheme::Secondary => palette.on_secondary_accent,
             },
             ..Default::default()
         }
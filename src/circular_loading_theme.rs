@@ -1,9 +1,6 @@
-use iced::{
-    widget::button::{self},
-    Theme, Vector,
-};
+use iced::Theme;
 
-use crate::{colours::{self, get_white}, sizes};
+use crate::colours::Palette;
 use crate::custom_widgets::circular_loading_spinner;
 
 pub enum CircularLoadingTheme {
@@ -14,14 +11,15 @@ pub enum CircularLoadingTheme {
 impl circular_loading_spinner::StyleSheet for CircularLoadingTheme {
     type Style = Theme;
 
-    fn appearance(&self, _style: &Theme) -> circular_loading_spinner::Appearance {
+    fn appearance(&self, style: &Theme) -> circular_loading_spinner::Appearance {
+        let palette = Palette::of(style);
         let colour = match self {
-            CircularLoadingTheme::Primary => colours::get_blue(),
-            CircularLoadingTheme::Secondary => colours::get_lilac(),
+            CircularLoadingTheme::Primary => palette.accent,
+            CircularLoadingTheme::Secondary => palette.secondary_accent,
         };
         circular_loading_spinner::Appearance {
             bar_color: colour,
-            track_color: colours::get_white(),
+            track_color: palette.background,
             ..Default::default()
         }
     }
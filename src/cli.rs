@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::resource_type::ResourceType;
+
+/// A desktop app for viewing and managing the workloads in a Kubernetes cluster
+#[derive(Debug, Clone, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Context to connect to, instead of the one that was open when the app was last closed
+    #[arg(long)]
+    pub context: Option<String>,
+    /// Namespace to open, instead of the tabs that were open when the app was last closed
+    #[arg(short, long, conflicts_with = "all_namespaces")]
+    pub namespace: Option<String>,
+    /// Kubeconfig file to read the contexts from, instead of `$KUBECONFIG` or `~/.kube/config`
+    #[arg(long)]
+    pub kubeconfig: Option<PathBuf>,
+    /// List the workloads in every namespace
+    #[arg(short = 'A', long)]
+    pub all_namespaces: bool,
+    /// Only list this type of resource, e.g. `pods` or `deploy`
+    #[arg(long)]
+    pub resource: Option<ResourceType>,
+    /// Most verbose level of messages that are logged: off, error, warn, info, debug or trace
+    #[arg(long, default_value_t = LevelFilter::INFO)]
+    pub log_level: LevelFilter,
+    /// File that the log is written to, instead of `iced-k8s/iced-k8s.log` in the cache directory
+    #[arg(long)]
+    pub log_file: Option<PathBuf>,
+}
+
+impl Cli {
+    /// # Returns
+    /// true if a particular view was asked for, in which case it's opened instead of the saved tabs
+    pub fn has_startup_view(&self) -> bool {
+        self.namespace.is_some() || self.all_namespaces || self.resource.is_some()
+    }
+}
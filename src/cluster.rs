@@ -1,22 +1,39 @@
+use std::collections::{BTreeMap, HashSet};
+
 use iced::{
-    advanced::widget::Operation,
-    alignment,
+    futures::future::{self, AbortHandle},
     widget::{
-        self, button, column, container, horizontal_space, row, text, text_input, vertical_rule,
+        button, column, container, horizontal_space, row, text, text_input, vertical_rule, Button,
+        Column, Row,
     },
-    Alignment, Background, Color, Command, Element, Length, Padding, Theme,
+    Alignment, Command, Element, Length, Padding,
 };
 
 use crate::{
+    audit::{Action, AuditEntry},
+    batch::{self, BatchAction, BatchResult},
     button_theme::{as_button_theme, ButtonTheme},
-    colours,
+    circular_loading_theme::{as_circular_theme, CircularLoadingTheme},
+    cluster_object::{ClusterObject, ObjectKey},
+    colours::Palette,
     container_theme::{as_container_theme, ContainerTheme},
-    custom_widgets::circular_loading_spinner::{self, Circular},
-    kube_context::KubeContext,
+    custom_widgets::{
+        circular_loading_spinner,
+        toast::{Status, Toast},
+    },
+    kube_context::{KubeContext, Safety},
     kube_interface,
     messages::{ClusterMessage, Message},
-    sizes, utils,
-    workloads::Workloads, circular_loading_theme::{as_circular_theme, CircularLoadingTheme},
+    metadata::{self, MetadataPatch},
+    metadata_editor::{MetadataEditor, Target},
+    permissions::Rules,
+    resource_type::ResourceType,
+    shortcuts::Shortcut,
+    sizes,
+    state::AppState,
+    tab::{TabId, TabRequest},
+    utils,
+    workloads::Workloads,
 };
 
 #[derive(Debug, Clone)]
@@ -27,49 +44,234 @@ enum View {
 
 #[derive(Debug, Clone)]
 pub struct Cluster {
+    id: TabId,
     context: KubeContext,
+    /// The only type of resource that's listed, or `None` to list every type
+    resource_type: Option<ResourceType>,
     workloads: Option<Workloads>,
     view: View,
     namespace_field_value: String,
+    /// The cluster object that the user is being asked to confirm the deletion of
+    pending_delete: Option<ClusterObject>,
+    /// The context name that was typed to confirm the pending delete, in a protected context
+    delete_confirmation_field_value: String,
+    /// Cancels the refresh that's in flight, so that a slow response can't overwrite a newer one
+    reload_handle: Option<AbortHandle>,
+    /// Numbers the refreshes, so that the result of one that was replaced is ignored
+    reload_count: u64,
+    /// Whether the last refresh failed, so that a failure is reported once rather than on every refresh
+    load_failed: bool,
+    /// Only objects whose name or namespace contains this are listed
+    filter_field_value: String,
+    /// The objects that batch actions are run on
+    selection: HashSet<ObjectKey>,
+    /// The object that was toggled last, which is where a range selection starts
+    selection_anchor: Option<ObjectKey>,
+    replicas_field_value: String,
+    /// A label as `key=value`
+    label_field_value: String,
+    /// The batch action that the user is being asked to confirm
+    pending_batch: Option<BatchAction>,
+    /// The context name that was typed to confirm the pending batch action, in a protected context
+    batch_confirmation_field_value: String,
+    batch_running: bool,
+    batch_result: Option<BatchResult>,
+    /// The detail pane for the labels and annotations of an object, or of the selection
+    metadata_editor: Option<MetadataEditor>,
+    /// The object that keyboard shortcuts act on
+    highlighted: Option<ObjectKey>,
 }
 
 impl Cluster {
-    pub fn new(context: KubeContext, workloads: Option<Workloads>) -> Cluster {
+    pub fn new(
+        id: TabId,
+        context: KubeContext,
+        resource_type: Option<ResourceType>,
+        workloads: Option<Workloads>,
+    ) -> Cluster {
         Cluster {
+            id,
             context,
+            resource_type,
             workloads,
             view: View::ListClusterItems,
             namespace_field_value: String::from(""),
+            pending_delete: None,
+            delete_confirmation_field_value: String::from(""),
+            reload_handle: None,
+            reload_count: 0,
+            load_failed: false,
+            filter_field_value: String::from(""),
+            selection: HashSet::new(),
+            selection_anchor: None,
+            replicas_field_value: String::from(""),
+            label_field_value: String::from(""),
+            pending_batch: None,
+            batch_confirmation_field_value: String::from(""),
+            batch_running: false,
+            batch_result: None,
+            metadata_editor: None,
+            highlighted: None,
+        }
+    }
+
+    pub fn get_id(&self) -> TabId {
+        self.id
+    }
+
+    pub fn get_context(&self) -> &KubeContext {
+        &self.context
+    }
+
+    pub fn get_resource_type(&self) -> Option<ResourceType> {
+        self.resource_type
+    }
+
+    /// What the user may do in the namespace, if it's known
+    fn rules(&self) -> Option<&Rules> {
+        self.workloads.as_ref().and_then(Workloads::get_rules)
+    }
+
+    fn cancel_reload(&mut self) {
+        if let Some(reload_handle) = self.reload_handle.take() {
+            reload_handle.abort();
+        }
+        // a refresh can finish before it's cancelled, and its result mustn't be used then
+        self.reload_count = self.reload_count.wrapping_add(1);
+    }
+
+    /// The id of the filter, so that it can be focused with a shortcut
+    fn filter_id(&self) -> text_input::Id {
+        text_input::Id::new(format!("filter-{}", self.id))
+    }
+
+    /// # Returns
+    /// The object that keyboard shortcuts act on, if it's still listed
+    fn highlighted_object(&self) -> Option<ClusterObject> {
+        let highlighted = self.highlighted.as_ref()?;
+        self.workloads.as_ref().and_then(|workloads| {
+            workloads
+                .matching(&self.filter_field_value)
+                .find(|cluster_object| cluster_object.key() == *highlighted)
+                .cloned()
+        })
+    }
+
+    /// Moves the highlight `offset` rows down the objects that match the filter, stopping at either end
+    fn move_highlight(&mut self, offset: isize) {
+        let Some(workloads) = &self.workloads else {
+            return;
+        };
+        let visible: Vec<ObjectKey> = workloads
+            .matching(&self.filter_field_value)
+            .map(ClusterObject::key)
+            .collect();
+        if visible.is_empty() {
+            return;
         }
+        let index = match self
+            .highlighted
+            .as_ref()
+            .and_then(|highlighted| visible.iter().position(|key| key == highlighted))
+        {
+            Some(index) => index.saturating_add_signed(offset).min(visible.len() - 1),
+            None => 0,
+        };
+        self.highlighted = Some(visible[index].clone());
     }
 
-    pub fn view(&self) -> Element<Message> {
+    /// Wraps the given message so that it's routed back to this tab
+    fn message(&self, message: ClusterMessage) -> Message {
+        Message::ClusterMessage(self.id, message)
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        state: &'a AppState,
+        unread_notifications: usize,
+    ) -> Element<'a, Message> {
+        let palette = state.settings.theme.palette();
+        let context_name = self.context.get_name();
+        let namespace = self.context.get_namespace();
         match self.view {
             View::ListClusterItems => {
+                // writing the namespace back only makes sense for a single namespace
+                let mut default_namespace_button =
+                    button(container(text("Make Default")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .style(as_button_theme(ButtonTheme::Secondary));
+                if !self.context.is_all_namespaces() {
+                    default_namespace_button =
+                        default_namespace_button.on_press(Message::KubeconfigNamespaceRequested(
+                            context_name.clone(),
+                            namespace.clone(),
+                        ));
+                }
+
                 let header = container(
                     container(
                         row![
-                            text("iced-k8s").size(sizes::H1).style(colours::get_white()),
+                            text("iced-k8s").size(sizes::H1).style(palette.on_accent),
                             vertical_rule(sizes::P),
-                            self.context.view(),
+                            self.context.view(palette),
                             horizontal_space(Length::Fill),
-                            button(container(text("Change Namespace")).padding(Padding {
+                            button(
+                                container(text(if unread_notifications > 0 {
+                                    format!("Notifications ({})", unread_notifications)
+                                } else {
+                                    String::from("Notifications")
+                                }))
+                                .padding(Padding {
+                                    bottom: 0.0,
+                                    top: 0.0,
+                                    left: sizes::SEP,
+                                    right: sizes::SEP,
+                                })
+                            )
+                            .on_press(Message::NotificationsRequested)
+                            .style(as_button_theme(ButtonTheme::Ghost)),
+                            button(
+                                container(text(
+                                    if state.is_favourite_namespace(&context_name, &namespace) {
+                                        "Unfavourite"
+                                    } else {
+                                        "Favourite"
+                                    }
+                                ))
+                                .padding(Padding {
+                                    bottom: 0.0,
+                                    top: 0.0,
+                                    left: sizes::SEP,
+                                    right: sizes::SEP,
+                                })
+                            )
+                            .on_press(Message::FavouriteNamespaceToggled(
+                                context_name.clone(),
+                                namespace.clone()
+                            ))
+                            .style(as_button_theme(ButtonTheme::Secondary)),
+                            default_namespace_button,
+                            button(container(text("Permissions")).padding(Padding {
                                 bottom: 0.0,
                                 top: 0.0,
                                 left: sizes::SEP,
                                 right: sizes::SEP,
                             }))
-                            .on_press(Message::ClusterMessage(
-                                ClusterMessage::ChangeNamespaceRequested
-                            ))
+                            .on_press(Message::OpenTab(TabRequest::Permissions(
+                                self.context.clone()
+                            )))
                             .style(as_button_theme(ButtonTheme::Secondary)),
-                            button(container(text("Change Context")).padding(Padding {
+                            button(container(text("Change Namespace")).padding(Padding {
                                 bottom: 0.0,
                                 top: 0.0,
                                 left: sizes::SEP,
                                 right: sizes::SEP,
                             }))
-                            .on_press(Message::ChangeContextRequested)
+                            .on_press(self.message(ClusterMessage::ChangeNamespaceRequested))
                             .style(as_button_theme(ButtonTheme::Secondary)),
                         ]
                         .width(Length::Fill)
@@ -87,17 +289,36 @@ impl Cluster {
                 .style(as_container_theme(ContainerTheme::Dark));
 
                 let workloads_content: Element<Message> = match &self.workloads {
-                    Some(workloads) => container(workloads.view())
-                        .padding(sizes::SEP)
-                        .style(as_container_theme(ContainerTheme::Light))
-                        .height(Length::Fill)
-                        .width(Length::Fill)
-                        .into(),
+                    Some(workloads) => container(workloads.view(
+                        self.id,
+                        &state.settings,
+                        self.context.is_all_namespaces(),
+                        self.context.get_safety(),
+                        &self.filter_field_value,
+                        &self.selection,
+                        self.highlighted.as_ref(),
+                    ))
+                    .padding(sizes::SEP)
+                    .style(as_container_theme(ContainerTheme::Light))
+                    .height(Length::Fill)
+                    .width(Length::Fill)
+                    .into(),
+                    None if self.load_failed => container(
+                        text("Unable to load workloads for this namespace").style(palette.danger),
+                    )
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .padding(sizes::SEP)
+                    .center_x()
+                    .center_y()
+                    .style(as_container_theme(ContainerTheme::Light))
+                    .into(),
                     None => container(
                         row![
-                            circular_loading_spinner::Circular::new().style(as_circular_theme(CircularLoadingTheme::Primary)),
+                            circular_loading_spinner::Circular::new()
+                                .style(as_circular_theme(CircularLoadingTheme::Primary)),
                             horizontal_space(sizes::SEP),
-                            text("Loading workloads...").style(colours::get_black())
+                            text("Loading workloads...").style(palette.text)
                         ]
                         .align_items(Alignment::Center),
                     )
@@ -110,7 +331,36 @@ impl Cluster {
                     .into(),
                 };
 
-                column![header, workloads_content]
+                let mut content = column![header, self.toolbar(state)];
+                if let Some(cluster_object) = &self.pending_delete {
+                    content = content.push(self.delete_confirmation(cluster_object, state));
+                }
+                if let Some(metadata_editor) = &self.metadata_editor {
+                    content = content.push(metadata_editor.view(&self.context, palette));
+                }
+                if let Some(action) = &self.pending_batch {
+                    content = content.push(self.batch_confirmation(action, palette));
+                }
+                if self.batch_running {
+                    content = content.push(
+                        container(
+                            text(format!(
+                                "Running the batch action on {} objects...",
+                                self.selection.len()
+                            ))
+                            .style(palette.text),
+                        )
+                        .width(Length::Fill)
+                        .padding(sizes::SEP)
+                        .style(as_container_theme(ContainerTheme::Light)),
+                    );
+                }
+                if let Some(batch_result) = &self.batch_result {
+                    content = content.push(self.batch_result_view(batch_result, palette));
+                }
+
+                content
+                    .push(workloads_content)
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .into()
@@ -124,22 +374,39 @@ impl Cluster {
                         right: sizes::SEP,
                     }));
                 set_namespace_button = if !self.namespace_field_value.is_empty() {
-                    set_namespace_button.on_press(Message::ClusterMessage(
-                        ClusterMessage::NamespaceSelected(self.namespace_field_value.clone()),
-                    ))
+                    set_namespace_button.on_press(self.message(ClusterMessage::NamespaceSelected(
+                        self.namespace_field_value.clone(),
+                    )))
                 } else {
                     set_namespace_button
                 };
 
+                let favourites: Vec<Element<Message>> = state
+                    .get_favourite_namespaces(&context_name)
+                    .iter()
+                    .map(|favourite| {
+                        button(container(text(favourite)).padding(Padding {
+                            top: 0.0,
+                            bottom: 0.0,
+                            left: sizes::SEP,
+                            right: sizes::SEP,
+                        }))
+                        .on_press(self.message(ClusterMessage::FavouriteNamespaceSelected(
+                            favourite.clone(),
+                        )))
+                        .style(as_button_theme(ButtonTheme::Secondary))
+                        .into()
+                    })
+                    .collect();
+
                 container(
                     column![
                         text("Pick a new namespace to use")
                             .size(sizes::H2)
-                            .style(colours::get_black()),
+                            .style(palette.text),
+                        Row::with_children(favourites).spacing(sizes::SEP),
                         text_input("New namespace", &self.namespace_field_value).on_input(
-                            |value| Message::ClusterMessage(ClusterMessage::NamespaceFieldChanged(
-                                value
-                            ))
+                            |value| self.message(ClusterMessage::NamespaceFieldChanged(value))
                         ),
                         row![
                             button(container(text("Change Context")).padding(Padding {
@@ -167,45 +434,874 @@ impl Cluster {
         }
     }
 
+    fn delete_confirmation<'a>(
+        &'a self,
+        cluster_object: &'a ClusterObject,
+        state: &AppState,
+    ) -> Element<'a, Message> {
+        let palette = state.settings.theme.palette();
+        let protected = self.context.get_safety() == Safety::Protected;
+        let mut delete_button = button(container(text("Delete").style(palette.on_accent)).padding(
+            Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            },
+        ))
+        .style(iced::theme::Button::Destructive);
+        if self
+            .context
+            .is_confirmed(&self.delete_confirmation_field_value)
+        {
+            delete_button = delete_button.on_press(self.message(ClusterMessage::DeleteRequested(
+                cluster_object.clone(),
+                state.settings.propagation_policy,
+            )));
+        }
+
+        let mut confirmation = row![
+            text(format!(
+                "Are you sure you want to delete {} {}?",
+                cluster_object.r#type, cluster_object.name
+            ))
+            .style(palette.text),
+            horizontal_space(Length::Fill),
+        ]
+        .spacing(sizes::SEP)
+        .align_items(Alignment::Center);
+        if protected {
+            confirmation = confirmation.push(
+                text_input(
+                    &format!("Type {} to confirm", self.context.get_name()),
+                    &self.delete_confirmation_field_value,
+                )
+                .on_input(|value| {
+                    self.message(ClusterMessage::DeleteConfirmationFieldChanged(value))
+                })
+                .width(300),
+            );
+        }
+        confirmation = confirmation
+            .push(
+                button(container(text("Cancel")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(self.message(ClusterMessage::DeleteCancelled))
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            )
+            .push(delete_button);
+
+        container(confirmation)
+            .width(Length::Fill)
+            .padding(sizes::SEP)
+            .style(as_container_theme(ContainerTheme::Light))
+            .into()
+    }
+
+    /// Filters the objects, and runs actions on the ones that are selected
+    fn toolbar(&self, state: &AppState) -> Element<'_, Message> {
+        let palette = state.settings.theme.palette();
+        let has_workloads = self.workloads.as_ref().is_some_and(|workloads| {
+            workloads
+                .matching(&self.filter_field_value)
+                .next()
+                .is_some()
+        });
+        let mut toolbar = row![
+            text_input("Filter by name or namespace", &self.filter_field_value)
+                .id(self.filter_id())
+                .on_input(|value| self.message(ClusterMessage::FilterChanged(value)))
+                .width(300),
+            toolbar_button(
+                "Select All Matching",
+                has_workloads.then(|| self.message(ClusterMessage::SelectAllMatchingRequested)),
+            )
+            .style(as_button_theme(ButtonTheme::Secondary)),
+        ]
+        .spacing(sizes::SEP)
+        .align_items(Alignment::Center);
+
+        if !self.selection.is_empty() {
+            // nothing can be changed while a batch is waiting to be confirmed or is running
+            let can_change = self.context.get_safety() != Safety::ReadOnly
+                && self.pending_batch.is_none()
+                && !self.batch_running;
+            // allowed unless the rules say otherwise for one of the selected types, since the rules might be incomplete
+            let rules = self.rules();
+            let allowed = |verb: &str| {
+                self.selection.iter().all(|(r#type, _, _)| {
+                    let api_resource = r#type.api_resource();
+                    rules.and_then(|rules| {
+                        rules.allows(verb, &api_resource.group, &api_resource.plural, None)
+                    }) != Some(false)
+                })
+            };
+            let can_patch = can_change && allowed("patch");
+            let can_delete = can_change && allowed("delete");
+            let request = |action: BatchAction| {
+                let permitted = match action {
+                    BatchAction::Delete(..) => can_delete,
+                    BatchAction::Restart | BatchAction::Scale(..) | BatchAction::Label(..) => {
+                        can_patch
+                    }
+                };
+                permitted.then(|| self.message(ClusterMessage::BatchRequested(action)))
+            };
+            let replicas = self.replicas_field_value.trim().parse::<i32>().ok();
+            let label = self
+                .label_field_value
+                .split_once('=')
+                .map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()))
+                .filter(|(key, value)| {
+                    metadata::check_key(key).is_ok() && metadata::check_label_value(value).is_ok()
+                });
+
+            toolbar = toolbar
+                .push(text(format!("{} selected", self.selection.len())).style(palette.text))
+                .push(
+                    toolbar_button(
+                        "Clear",
+                        Some(self.message(ClusterMessage::SelectionCleared)),
+                    )
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                )
+                .push(horizontal_space(Length::Fill))
+                .push(
+                    toolbar_button("Restart", request(BatchAction::Restart))
+                        .style(as_button_theme(ButtonTheme::Secondary)),
+                )
+                .push(
+                    text_input("Replicas", &self.replicas_field_value)
+                        .on_input(|value| self.message(ClusterMessage::ReplicasFieldChanged(value)))
+                        .width(80),
+                )
+                .push(
+                    toolbar_button(
+                        "Scale",
+                        replicas
+                            .filter(|replicas| *replicas >= 0)
+                            .and_then(|replicas| request(BatchAction::Scale(replicas))),
+                    )
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                )
+                .push(
+                    text_input("key=value", &self.label_field_value)
+                        .on_input(|value| self.message(ClusterMessage::LabelFieldChanged(value)))
+                        .width(160),
+                )
+                .push(
+                    toolbar_button(
+                        "Label",
+                        label.and_then(|(key, value)| {
+                            request(BatchAction::Label(MetadataPatch {
+                                labels: BTreeMap::from([(key, Some(value))]),
+                                ..MetadataPatch::default()
+                            }))
+                        }),
+                    )
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                )
+                .push(
+                    toolbar_button(
+                        "Edit Labels...",
+                        can_patch.then(|| {
+                            self.message(ClusterMessage::SelectionMetadataEditorRequested)
+                        }),
+                    )
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                )
+                .push(
+                    toolbar_button(
+                        "Delete",
+                        request(BatchAction::Delete(state.settings.propagation_policy)),
+                    )
+                    .style(iced::theme::Button::Destructive),
+                );
+        }
+
+        container(toolbar)
+            .width(Length::Fill)
+            .padding(sizes::SEP)
+            .style(as_container_theme(ContainerTheme::Light))
+            .into()
+    }
+
+    /// Batch actions are always confirmed, since they can change a lot at once
+    fn batch_confirmation<'a>(
+        &'a self,
+        action: &BatchAction,
+        palette: Palette,
+    ) -> Element<'a, Message> {
+        let protected = self.context.get_safety() == Safety::Protected;
+        let context_name = self.context.get_name();
+        let confirm_button = toolbar_button(
+            "Confirm",
+            self.context
+                .is_confirmed(&self.batch_confirmation_field_value)
+                .then(|| self.message(ClusterMessage::BatchConfirmed)),
+        )
+        .style(iced::theme::Button::Destructive);
+
+        let mut confirmation = row![
+            text(format!(
+                "Are you sure you want to run \"{}\" on {} objects?",
+                action,
+                self.selection.len()
+            ))
+            .style(palette.text),
+            horizontal_space(Length::Fill),
+        ]
+        .spacing(sizes::SEP)
+        .align_items(Alignment::Center);
+        if protected {
+            confirmation = confirmation.push(
+                text_input(
+                    &format!("Type {} to confirm", context_name),
+                    &self.batch_confirmation_field_value,
+                )
+                .on_input(|value| {
+                    self.message(ClusterMessage::BatchConfirmationFieldChanged(value))
+                })
+                .width(300),
+            );
+        }
+
+        container(
+            confirmation
+                .push(
+                    toolbar_button("Cancel", Some(self.message(ClusterMessage::BatchCancelled)))
+                        .style(as_button_theme(ButtonTheme::Secondary)),
+                )
+                .push(confirm_button),
+        )
+        .width(Length::Fill)
+        .padding(sizes::SEP)
+        .style(as_container_theme(ContainerTheme::Light))
+        .into()
+    }
+
+    /// Says how many objects the batch action worked on, and why it failed on the rest
+    fn batch_result_view<'a>(
+        &'a self,
+        batch_result: &'a BatchResult,
+        palette: Palette,
+    ) -> Element<'a, Message> {
+        let failures: Vec<Element<Message>> = batch_result
+            .results
+            .iter()
+            .filter_map(|(cluster_object, res)| {
+                res.as_ref().err().map(|error| {
+                    text(format!(
+                        "{} {}: {}",
+                        cluster_object.r#type,
+                        cluster_object.name,
+                        error.get_message()
+                    ))
+                    .size(sizes::P)
+                    .style(palette.danger)
+                    .into()
+                })
+            })
+            .collect();
+
+        container(
+            column![
+                row![
+                    text(format!(
+                        "{}: {} succeeded, {} failed",
+                        batch_result.action,
+                        batch_result.succeeded(),
+                        batch_result.failed()
+                    ))
+                    .style(palette.text),
+                    horizontal_space(Length::Fill),
+                    toolbar_button(
+                        "Dismiss",
+                        Some(self.message(ClusterMessage::BatchResultDismissed))
+                    )
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                ]
+                .spacing(sizes::SEP)
+                .align_items(Alignment::Center),
+                Column::with_children(failures).spacing(sizes::SMOL_SEP),
+            ]
+            .spacing(sizes::SMOL_SEP),
+        )
+        .width(Length::Fill)
+        .padding(sizes::SEP)
+        .style(as_container_theme(ContainerTheme::Light))
+        .into()
+    }
+
     pub fn update(&mut self, message: ClusterMessage) -> iced::Command<Message> {
+        let id = self.id;
+        if let ClusterMessage::WorkloadsLoaded(reload, ..) = message {
+            if reload != self.reload_count {
+                return Command::none();
+            }
+            self.reload_handle = None;
+        }
         match message {
-            ClusterMessage::WorkloadsLoaded(Ok(workloads)) => {
+            ClusterMessage::WorkloadsLoaded(_, Ok(workloads)) => {
+                // objects that are gone can't be acted on anymore
+                let keys: HashSet<ObjectKey> = workloads
+                    .get_cluster_objects()
+                    .iter()
+                    .map(ClusterObject::key)
+                    .collect();
+                self.selection.retain(|key| keys.contains(key));
+                // nothing can be shown in a namespace where nothing can be listed, so ask for another one
+                let inaccessible = self.workloads.is_none()
+                    && workloads.is_all_forbidden(self.resource_type)
+                    && !self.context.is_all_namespaces();
                 self.workloads = Some(workloads);
+                self.load_failed = false;
+                if inaccessible {
+                    let toast = Toast::new(
+                        Status::Danger,
+                        "Not permitted",
+                        format!(
+                            "You aren't permitted to list anything in the namespace {}. Pick a different namespace.",
+                            self.context.get_namespace()
+                        ),
+                    );
+                    return Command::batch(vec![
+                        Command::perform(utils::resolved(), move |_ignored| {
+                            Message::AddToast(toast)
+                        }),
+                        Command::perform(utils::resolved(), move |_ignored| {
+                            Message::ClusterMessage(id, ClusterMessage::ChangeNamespaceRequested)
+                        }),
+                    ]);
+                }
 
                 Command::none()
             }
-            ClusterMessage::WorkloadsLoaded(Err(error)) => {
-                self.workloads = None;
-                println!("{}", error.get_message());
+            ClusterMessage::WorkloadsLoaded(_, Err(error)) if error.is_auth_expired() => {
+                // keep showing the last workloads, since the namespace is fine and the connection asks to log in again
+                let context_name = self.context.get_name();
+                Command::perform(utils::resolved(), move |_ignored| {
+                    Message::AuthExpired(context_name)
+                })
+            }
+            // the refresh keeps failing the same way, e.g. while the cluster can't be reached
+            ClusterMessage::WorkloadsLoaded(_, Err(..)) if self.load_failed => Command::none(),
+            ClusterMessage::WorkloadsLoaded(_, Err(error)) => {
+                // the last workloads stay listed, since the failure might be temporary
+                self.load_failed = true;
+                tracing::warn!("Unable to load workloads: {}", error.get_message());
+
+                let toast = error.to_toast("Unable to load workloads for this namespace.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            ClusterMessage::ReloadRequested => match self.view {
+                View::ListClusterItems => {
+                    self.cancel_reload();
+                    let (fetch, reload_handle) =
+                        future::abortable(kube_interface::fetch_cluster_state(
+                            self.context.clone(),
+                            self.resource_type,
+                        ));
+                    self.reload_handle = Some(reload_handle);
+                    let reload = self.reload_count;
+
+                    Command::perform(fetch, move |res| match res {
+                        Ok(res) => Message::ClusterMessage(
+                            id,
+                            ClusterMessage::WorkloadsLoaded(reload, res),
+                        ),
+                        Err(..) => Message::ClusterMessage(id, ClusterMessage::ReloadCancelled),
+                    })
+                }
+                View::SetNamespace => Command::none(),
+            },
+            ClusterMessage::ReloadCancelled => {
+                tracing::debug!(tab = id, "cancelled a refresh that was superseded");
+
+                Command::none()
+            }
+            ClusterMessage::DeleteConfirmationRequested(cluster_object) => {
+                self.pending_delete = Some(cluster_object);
+                self.delete_confirmation_field_value = String::from("");
+
+                Command::none()
+            }
+            ClusterMessage::DeleteConfirmationFieldChanged(value) => {
+                self.delete_confirmation_field_value = value;
+
+                Command::none()
+            }
+            ClusterMessage::DeleteCancelled => {
+                self.pending_delete = None;
+
+                Command::none()
+            }
+            ClusterMessage::DeleteRequested(cluster_object, propagation_policy) => {
+                // the buttons are disabled too, but the context could have become read-only since
+                if self.context.is_read_only() {
+                    self.pending_delete = None;
+                    return Command::none();
+                }
+                // however the delete was asked for, a protected context needs its name typed first
+                if !self
+                    .context
+                    .is_confirmed(&self.delete_confirmation_field_value)
+                {
+                    return self
+                        .update(ClusterMessage::DeleteConfirmationRequested(cluster_object));
+                }
+                self.pending_delete = None;
+                self.delete_confirmation_field_value = String::from("");
+
+                Command::perform(
+                    kube_interface::delete(
+                        self.context.clone(),
+                        cluster_object.clone(),
+                        propagation_policy,
+                    ),
+                    move |res| {
+                        Message::ClusterMessage(id, ClusterMessage::Deleted(cluster_object, res))
+                    },
+                )
+            }
+            ClusterMessage::Deleted(cluster_object, result) => {
+                let entry = AuditEntry::new(
+                    &self.context,
+                    cluster_object.namespace.clone(),
+                    Action::Delete,
+                    cluster_object.r#type,
+                    cluster_object.name.clone(),
+                    result.as_ref().map(|_snapshot| ()),
+                );
+                let toast = match result {
+                    Err(error) => {
+                        tracing::warn!("Unable to delete resource: {}", error.get_message());
+                        error.to_toast("Failed to delete resource.")
+                    }
+                    Ok(snapshot) => {
+                        let toast = Toast::new(
+                            Status::Success,
+                            "Deleted",
+                            format!("Deleted {} {}", cluster_object.r#type, cluster_object.name),
+                        );
+                        match snapshot {
+                            // the undo button goes away with the toast, which keeps the window short
+                            Some(snapshot) => toast.action(
+                                "Undo",
+                                Message::RecreateRequested(
+                                    self.context.clone(),
+                                    cluster_object,
+                                    snapshot,
+                                ),
+                            ),
+                            None => toast,
+                        }
+                    }
+                };
 
                 Command::batch(vec![
                     Command::perform(utils::resolved(), move |_ignored| {
-                        Message::AddToast(
-                            "Unable to load workloads for this namespace. Please select a different namespace or context".into(),
-                        )
+                        Message::ActionPerformed(entry)
                     }),
-                    Command::perform(utils::resolved(), |_ignored| {
-                        ClusterMessage::ChangeNamespaceRequested.into()
+                    Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast)),
+                ])
+            }
+            ClusterMessage::LogsRequested(cluster_object) => {
+                let context = self
+                    .context
+                    .with_namespace(cluster_object.namespace.clone());
+                Command::perform(utils::resolved(), move |_ignored| {
+                    Message::OpenTab(TabRequest::PodLogs(context, cluster_object.name))
+                })
+            }
+            ClusterMessage::YamlRequested(cluster_object) => {
+                let context = self
+                    .context
+                    .with_namespace(cluster_object.namespace.clone());
+                Command::perform(utils::resolved(), move |_ignored| {
+                    Message::OpenTab(TabRequest::YamlEditor(context, cluster_object))
+                })
+            }
+            ClusterMessage::FilterChanged(value) => {
+                self.filter_field_value = value;
+
+                Command::none()
+            }
+            ClusterMessage::SelectionToggled {
+                key,
+                selected,
+                range,
+            } => {
+                let visible: Vec<ObjectKey> = self
+                    .workloads
+                    .as_ref()
+                    .map(|workloads| {
+                        workloads
+                            .matching(&self.filter_field_value)
+                            .map(ClusterObject::key)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let anchor = self
+                    .selection_anchor
+                    .as_ref()
+                    .and_then(|anchor| visible.iter().position(|visible| visible == anchor));
+                let clicked = visible.iter().position(|visible| *visible == key);
+                let keys = match (range, anchor, clicked) {
+                    (true, Some(anchor), Some(clicked)) => {
+                        visible[anchor.min(clicked)..=anchor.max(clicked)].to_vec()
+                    }
+                    _ => vec![key.clone()],
+                };
+                for key in keys {
+                    if selected {
+                        self.selection.insert(key);
+                    } else {
+                        self.selection.remove(&key);
+                    }
+                }
+                self.selection_anchor = Some(key);
+
+                Command::none()
+            }
+            ClusterMessage::SelectAllMatchingRequested => {
+                if let Some(workloads) = &self.workloads {
+                    self.selection.extend(
+                        workloads
+                            .matching(&self.filter_field_value)
+                            .map(ClusterObject::key),
+                    );
+                }
+
+                Command::none()
+            }
+            ClusterMessage::SelectionCleared => {
+                self.selection.clear();
+                self.selection_anchor = None;
+
+                Command::none()
+            }
+            ClusterMessage::ReplicasFieldChanged(value) => {
+                self.replicas_field_value = value;
+
+                Command::none()
+            }
+            ClusterMessage::LabelFieldChanged(value) => {
+                self.label_field_value = value;
+
+                Command::none()
+            }
+            ClusterMessage::BatchRequested(action) => {
+                self.pending_batch = Some(action);
+                self.batch_confirmation_field_value = String::from("");
+
+                Command::none()
+            }
+            ClusterMessage::BatchConfirmationFieldChanged(value) => {
+                self.batch_confirmation_field_value = value;
+
+                Command::none()
+            }
+            ClusterMessage::BatchCancelled => {
+                self.pending_batch = None;
+
+                Command::none()
+            }
+            ClusterMessage::BatchConfirmed => {
+                // however it was confirmed, a protected context needs its name typed first
+                if !self
+                    .context
+                    .is_confirmed(&self.batch_confirmation_field_value)
+                {
+                    return Command::none();
+                }
+                let Some(action) = self.pending_batch.take() else {
+                    return Command::none();
+                };
+                self.batch_confirmation_field_value = String::from("");
+                if self.context.is_read_only() {
+                    return Command::none();
+                }
+                let cluster_objects: Vec<ClusterObject> = self
+                    .workloads
+                    .as_ref()
+                    .map(|workloads| {
+                        workloads
+                            .get_cluster_objects()
+                            .iter()
+                            .filter(|cluster_object| self.selection.contains(&cluster_object.key()))
+                            .cloned()
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                self.batch_running = true;
+                self.batch_result = None;
+
+                Command::perform(
+                    batch::run(self.context.clone(), cluster_objects, action),
+                    move |res| Message::ClusterMessage(id, ClusterMessage::BatchFinished(res)),
+                )
+            }
+            ClusterMessage::BatchFinished(batch_result) => {
+                self.batch_running = false;
+                let action = batch_result.action.audit_action();
+                let mut commands: Vec<Command<Message>> = batch_result
+                    .results
+                    .iter()
+                    .map(|(cluster_object, res)| {
+                        let entry = AuditEntry::new(
+                            &self.context,
+                            cluster_object.namespace.clone(),
+                            action,
+                            cluster_object.r#type,
+                            cluster_object.name.clone(),
+                            res.as_ref().copied(),
+                        );
+                        Command::perform(utils::resolved(), move |_ignored| {
+                            Message::ActionPerformed(entry)
+                        })
+                    })
+                    .collect();
+                // keep the ones that failed selected, so that they can be tried again
+                self.selection = batch_result
+                    .results
+                    .iter()
+                    .filter(|(_, res)| res.is_err())
+                    .map(|(cluster_object, _)| cluster_object.key())
+                    .collect();
+                self.batch_result = Some(batch_result);
+                commands.push(self.update(ClusterMessage::ReloadRequested));
+
+                Command::batch(commands)
+            }
+            ClusterMessage::BatchResultDismissed => {
+                self.batch_result = None;
+
+                Command::none()
+            }
+            ClusterMessage::MetadataEditorRequested(cluster_object) => {
+                let key = cluster_object.key();
+                self.metadata_editor = Some(MetadataEditor::new(
+                    id,
+                    Target::Object(cluster_object.clone()),
+                ));
+
+                Command::perform(
+                    kube_interface::fetch_metadata(self.context.clone(), cluster_object),
+                    move |res| {
+                        Message::ClusterMessage(id, ClusterMessage::MetadataLoaded(key, res))
+                    },
+                )
+            }
+            ClusterMessage::SelectionMetadataEditorRequested => {
+                self.metadata_editor = Some(MetadataEditor::new(
+                    id,
+                    Target::Selection(self.selection.len()),
+                ));
+
+                Command::none()
+            }
+            ClusterMessage::MetadataLoaded(key, res) => {
+                // the editor may have been closed, or opened for another object, in the meantime
+                let Some(metadata_editor) = self.metadata_editor.as_mut().filter(|editor| {
+                    matches!(editor.get_target(), Target::Object(cluster_object) if cluster_object.key() == key)
+                }) else {
+                    return Command::none();
+                };
+                match res {
+                    Ok(metadata) => {
+                        metadata_editor.loaded(metadata);
+
+                        Command::none()
+                    }
+                    Err(error) => {
+                        self.metadata_editor = None;
+                        tracing::warn!(
+                            "Unable to load labels and annotations: {}",
+                            error.get_message()
+                        );
+                        let toast = error.to_toast("Unable to load the labels and annotations.");
+
+                        Command::perform(utils::resolved(), move |_ignored| {
+                            Message::AddToast(toast)
+                        })
+                    }
+                }
+            }
+            ClusterMessage::MetadataEditorMessage(message) => {
+                if let Some(metadata_editor) = self.metadata_editor.as_mut() {
+                    metadata_editor.update(message);
+                }
+
+                Command::none()
+            }
+            ClusterMessage::MetadataEditorCancelled => {
+                self.metadata_editor = None;
+
+                Command::none()
+            }
+            ClusterMessage::MetadataApplyRequested => {
+                let context = self.context.clone();
+                let Some(metadata_editor) = self
+                    .metadata_editor
+                    .as_mut()
+                    .filter(|editor| editor.can_apply(&context))
+                else {
+                    return Command::none();
+                };
+                let Some(patch) = metadata_editor.patch() else {
+                    return Command::none();
+                };
+                match metadata_editor.get_target().clone() {
+                    Target::Object(cluster_object) => {
+                        metadata_editor.set_applying(true);
+
+                        Command::perform(
+                            kube_interface::patch_metadata(
+                                self.context.clone(),
+                                cluster_object.clone(),
+                                patch,
+                            ),
+                            move |res| {
+                                Message::ClusterMessage(
+                                    id,
+                                    ClusterMessage::MetadataApplied(cluster_object, res),
+                                )
+                            },
+                        )
+                    }
+                    // the selection goes through the same confirmation as every other batch action
+                    Target::Selection(..) => {
+                        self.metadata_editor = None;
+
+                        self.update(ClusterMessage::BatchRequested(BatchAction::Label(patch)))
+                    }
+                }
+            }
+            ClusterMessage::MetadataApplied(cluster_object, res) => {
+                let entry = AuditEntry::new(
+                    &self.context,
+                    cluster_object.namespace.clone(),
+                    Action::Label,
+                    cluster_object.r#type,
+                    cluster_object.name.clone(),
+                    res.as_ref().copied(),
+                );
+                // another object may have been opened in the editor in the meantime
+                let key = cluster_object.key();
+                let editing = self.metadata_editor.as_ref().is_some_and(|editor| {
+                    matches!(editor.get_target(), Target::Object(editing) if editing.key() == key)
+                });
+                let toast = match res {
+                    Ok(()) => {
+                        if editing {
+                            self.metadata_editor = None;
+                        }
+                        Toast::new(
+                            Status::Success,
+                            "Updated",
+                            format!(
+                                "Updated the labels and annotations of {} {}",
+                                cluster_object.r#type, cluster_object.name
+                            ),
+                        )
+                    }
+                    Err(error) => {
+                        // keep the editor open, so that the changes can be fixed and applied again
+                        if let Some(metadata_editor) =
+                            self.metadata_editor.as_mut().filter(|_| editing)
+                        {
+                            metadata_editor.set_applying(false);
+                        }
+                        tracing::warn!(
+                            "Unable to update labels and annotations: {}",
+                            error.get_message()
+                        );
+                        error.to_toast("Failed to update the labels and annotations.")
+                    }
+                };
+
+                Command::batch(vec![
+                    Command::perform(utils::resolved(), move |_ignored| {
+                        Message::ActionPerformed(entry)
                     }),
+                    Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast)),
                 ])
             }
-            ClusterMessage::ReloadRequested => match self.view {
-                View::ListClusterItems => Command::perform(
-                    kube_interface::fetch_cluster_state(self.context.clone()),
-                    |res| Message::ClusterMessage(ClusterMessage::WorkloadsLoaded(res)),
-                ),
-                View::SetNamespace => Command::none(),
-            },
-            ClusterMessage::DeleteRequested(cluster_object) => Command::perform(
-                kube_interface::delete(self.context.clone(), cluster_object),
-                |res| Message::ClusterMessage(ClusterMessage::Deleted(res)),
-            ),
-            ClusterMessage::Deleted(result) => match result {
-                Err(_error) => Command::perform(utils::resolved(), |_ignored| {
-                    Message::AddToast(String::from("Failed to delete resource"))
-                }),
-                Ok(..) => Command::none(),
-            },
+            ClusterMessage::ShortcutPressed(shortcut) => {
+                if let View::SetNamespace = self.view {
+                    return Command::none();
+                }
+                match shortcut {
+                    Shortcut::Next => {
+                        self.move_highlight(1);
+
+                        Command::none()
+                    }
+                    Shortcut::Previous => {
+                        self.move_highlight(-1);
+
+                        Command::none()
+                    }
+                    Shortcut::Open => match self.highlighted_object() {
+                        Some(cluster_object) => {
+                            self.update(ClusterMessage::YamlRequested(cluster_object))
+                        }
+                        None => Command::none(),
+                    },
+                    Shortcut::Logs => match self.highlighted_object() {
+                        Some(cluster_object)
+                            if cluster_object.r#type == ResourceType::Pod
+                                && cluster_object.is_allowed(self.rules(), "get", Some("log")) =>
+                        {
+                            self.update(ClusterMessage::LogsRequested(cluster_object))
+                        }
+                        _ => Command::none(),
+                    },
+                    // a single key press is easy to hit by accident, so this always asks first
+                    Shortcut::Delete => match self.highlighted_object() {
+                        Some(cluster_object)
+                            if !self.context.is_read_only()
+                                && cluster_object.is_allowed(self.rules(), "delete", None) =>
+                        {
+                            self.update(ClusterMessage::DeleteConfirmationRequested(cluster_object))
+                        }
+                        _ => Command::none(),
+                    },
+                    Shortcut::Filter => text_input::focus(self.filter_id()),
+                    Shortcut::Escape => {
+                        if self.pending_delete.is_some() {
+                            self.pending_delete = None;
+                        } else if self.pending_batch.is_some() {
+                            return self.update(ClusterMessage::BatchCancelled);
+                        } else if self.metadata_editor.is_some() {
+                            self.metadata_editor = None;
+                        } else {
+                            self.highlighted = None;
+                        }
+
+                        Command::none()
+                    }
+                    // the palette belongs to the whole app
+                    Shortcut::CommandPalette => Command::none(),
+                }
+            }
+            ClusterMessage::ResourceTypeSelected(resource_type) => {
+                self.resource_type = resource_type;
+                self.view = View::ListClusterItems;
+                self.workloads = None;
+                self.load_failed = false;
+                self.selection.clear();
+                self.selection_anchor = None;
+                self.highlighted = None;
+
+                self.update(ClusterMessage::ReloadRequested)
+            }
             ClusterMessage::ChangeNamespaceRequested => {
                 self.view = View::SetNamespace;
 
@@ -216,35 +1312,65 @@ impl Cluster {
 
                 Command::none()
             }
+            ClusterMessage::FavouriteNamespaceSelected(new_namespace) => {
+                self.namespace_field_value = new_namespace.clone();
+
+                self.update(ClusterMessage::NamespaceSelected(new_namespace))
+            }
             ClusterMessage::NamespaceSelected(new_namespace) => Command::perform(
-                kube_interface::check_namespace_accessible(KubeContext::new(
-                    self.context.get_config().clone(),
-                    new_namespace,
-                )),
-                |res| ClusterMessage::NamespaceChecked(res).into(),
+                kube_interface::check_namespace_accessible(
+                    self.context.with_namespace(new_namespace.clone()),
+                ),
+                move |res| {
+                    Message::ClusterMessage(
+                        id,
+                        ClusterMessage::NamespaceChecked(new_namespace, res),
+                    )
+                },
             ),
-            ClusterMessage::NamespaceChecked(res) => match res {
+            ClusterMessage::NamespaceChecked(namespace, res) => match res {
                 Ok(..) => {
-                    self.context = KubeContext::new(
-                        self.context.get_config().clone(),
-                        self.namespace_field_value.clone(),
-                    );
+                    // the refresh in flight is for the old namespace
+                    self.cancel_reload();
+                    self.context = self.context.with_namespace(namespace);
                     self.view = View::ListClusterItems;
                     self.namespace_field_value = String::from("");
+                    self.workloads = None;
+                    self.load_failed = false;
 
-                    Command::perform(utils::resolved(), |_ignored| {
-                        ClusterMessage::ReloadRequested.into()
-                    })
+                    let context_name = self.context.get_name();
+                    let namespace = self.context.get_namespace();
+                    Command::batch(vec![
+                        Command::perform(utils::resolved(), move |_ignored| {
+                            Message::ClusterMessage(id, ClusterMessage::ReloadRequested)
+                        }),
+                        Command::perform(utils::resolved(), move |_ignored| {
+                            Message::NamespaceChanged(context_name, namespace)
+                        }),
+                    ])
                 }
                 Err(error) => {
                     self.namespace_field_value = String::from("");
-                    println!("{}", error.get_message());
+                    tracing::warn!("Unable to access namespace: {}", error.get_message());
 
-                    Command::perform(utils::resolved(), |_ignored| {
-                        Message::AddToast(String::from("The given namespace couldn't be accessed. Make sure it exists and you have permission to access it."))
-                    })
+                    let toast = error.to_toast("The given namespace couldn't be accessed.");
+
+                    Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
                 }
             },
         }
     }
 }
+
+fn toolbar_button(label: &str, on_press: Option<Message>) -> Button<'_, Message> {
+    let button = button(container(text(label)).padding(Padding {
+        bottom: 0.0,
+        top: 0.0,
+        left: sizes::SEP,
+        right: sizes::SEP,
+    }));
+    match on_press {
+        Some(message) => button.on_press(message),
+        None => button,
+    }
+}
@@ -1,65 +1,190 @@
 use iced::{
-    widget::{button, container, horizontal_space, row, text},
+    widget::{button, checkbox, container, horizontal_space, text, Row},
     Alignment, Element, Length, Padding,
 };
-use k8s_openapi::api::{
-    apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
-    core::v1::Pod,
-};
-use kube::{api::DeleteParams, Api, Client};
 
 use crate::{
-    colours, error::Error, kube_context::KubeContext, messages::ClusterMessage,
-    resource_type::ResourceType, sizes, Message,
+    button_theme::{as_button_theme, ButtonTheme},
+    kube_context::Safety,
+    messages::ClusterMessage,
+    permissions::Rules,
+    resource_type::ResourceType,
+    settings::UserSettings,
+    sizes,
+    tab::TabId,
+    Message,
 };
 
+/// Identifies an object across refreshes, by its type, namespace and name
+pub type ObjectKey = (ResourceType, String, String);
+
 #[derive(Debug, Clone)]
 pub struct ClusterObject {
     pub name: String,
+    pub namespace: String,
     pub r#type: ResourceType,
     pub details: Option<String>,
-    pub children: Vec<ClusterObject>,
 }
 
 impl ClusterObject {
-    pub fn new(name: String, r#type: ResourceType, details: Option<String>) -> ClusterObject {
+    pub fn new(
+        name: String,
+        namespace: String,
+        r#type: ResourceType,
+        details: Option<String>,
+    ) -> ClusterObject {
         ClusterObject {
             name,
+            namespace,
             r#type,
-            children: vec![],
             details,
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
-        row![
-            text(self.name.to_owned())
-                .size(sizes::P)
-                .width(400)
-                .style(colours::get_black()),
-            text(self.r#type.to_owned())
-                .size(sizes::P)
-                .style(colours::get_grey())
-                .width(100),
-            text(self.details.clone().unwrap_or(String::from("")))
-                .style(colours::get_grey())
-                .width(100),
-            horizontal_space(Length::Fill),
-            button(
-                container(text("Delete").style(colours::get_white())).padding(Padding {
+    pub fn key(&self) -> ObjectKey {
+        (self.r#type, self.namespace.clone(), self.name.clone())
+    }
+
+    /// Whether the name or namespace contains `filter`, ignoring case
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        self.name.to_lowercase().contains(&filter)
+            || self.namespace.to_lowercase().contains(&filter)
+    }
+
+    /// Whether `rules` allow `verb` on this object, or on its `subresource` (e.g. `log`) if one is given.
+    /// It's allowed unless the rules say otherwise, since the rules might be incomplete.
+    pub fn is_allowed(&self, rules: Option<&Rules>, verb: &str, subresource: Option<&str>) -> bool {
+        let api_resource = self.r#type.api_resource();
+        let resource = match subresource {
+            Some(subresource) => format!("{}/{}", api_resource.plural, subresource),
+            None => api_resource.plural,
+        };
+        rules.and_then(|rules| rules.allows(verb, &api_resource.group, &resource, Some(&self.name)))
+            != Some(false)
+    }
+
+    /// `show_namespace` should be set when objects from several namespaces are listed together.
+    /// Actions that `rules` say the user may not do, or that `safety` doesn't allow, are disabled.
+    pub fn view(
+        &self,
+        tab_id: TabId,
+        settings: &UserSettings,
+        show_namespace: bool,
+        rules: Option<&Rules>,
+        safety: Safety,
+        selected: bool,
+    ) -> Element<'_, Message> {
+        let palette = settings.theme.palette();
+        // protected contexts always ask, since the context's name has to be typed to confirm
+        let delete_message = if settings.confirm_delete || safety == Safety::Protected {
+            ClusterMessage::DeleteConfirmationRequested(self.to_owned())
+        } else {
+            ClusterMessage::DeleteRequested(self.to_owned(), settings.propagation_policy)
+        };
+
+        let mut actions = Row::new().spacing(sizes::SEP);
+        if self.r#type == ResourceType::Pod {
+            let mut logs_button = button(container(text("Logs")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }))
+            .style(as_button_theme(ButtonTheme::Secondary));
+            if self.is_allowed(rules, "get", Some("log")) {
+                logs_button = logs_button.on_press(Message::ClusterMessage(
+                    tab_id,
+                    ClusterMessage::LogsRequested(self.to_owned()),
+                ));
+            }
+            actions = actions.push(logs_button);
+        }
+        let mut delete_button = button(container(text("Delete").style(palette.on_accent)).padding(
+            Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            },
+        ))
+        .style(iced::theme::Button::Destructive);
+        if safety != Safety::ReadOnly && self.is_allowed(rules, "delete", None) {
+            delete_button = delete_button.on_press(Message::ClusterMessage(tab_id, delete_message));
+        }
+        let mut labels_button = button(container(text("Labels")).padding(Padding {
+            bottom: 0.0,
+            top: 0.0,
+            left: sizes::SEP,
+            right: sizes::SEP,
+        }))
+        .style(as_button_theme(ButtonTheme::Secondary));
+        if safety != Safety::ReadOnly && self.is_allowed(rules, "patch", None) {
+            labels_button = labels_button.on_press(Message::ClusterMessage(
+                tab_id,
+                ClusterMessage::MetadataEditorRequested(self.to_owned()),
+            ));
+        }
+        actions = actions
+            .push(
+                button(container(text("YAML")).padding(Padding {
                     bottom: 0.0,
                     top: 0.0,
                     left: sizes::SEP,
                     right: sizes::SEP,
-                })
+                }))
+                .style(as_button_theme(ButtonTheme::Secondary))
+                .on_press(Message::ClusterMessage(
+                    tab_id,
+                    ClusterMessage::YamlRequested(self.to_owned()),
+                )),
+            )
+            .push(labels_button)
+            .push(delete_button);
+
+        let key = self.key();
+        let mut columns = Row::new()
+            .push(checkbox("", selected, move |selected| {
+                Message::ClusterMessage(
+                    tab_id,
+                    ClusterMessage::SelectionToggled {
+                        key: key.clone(),
+                        selected,
+                        range: false,
+                    },
+                )
+            }))
+            .push(
+                text(self.name.to_owned())
+                    .size(sizes::P)
+                    .width(400)
+                    .style(palette.text),
+            )
+            .spacing(sizes::P * 2.0)
+            .align_items(Alignment::Center);
+        if show_namespace {
+            columns = columns.push(
+                text(self.namespace.to_owned())
+                    .size(sizes::P)
+                    .style(palette.secondary_text)
+                    .width(200),
+            );
+        }
+
+        columns
+            .push(
+                text(self.r#type.to_owned())
+                    .size(sizes::P)
+                    .style(palette.secondary_text)
+                    .width(100),
+            )
+            .push(
+                text(self.details.clone().unwrap_or(String::from("")))
+                    .style(palette.secondary_text)
+                    .width(100),
             )
-            .style(iced::theme::Button::Destructive)
-            .on_press(Message::ClusterMessage(ClusterMessage::DeleteRequested(
-                self.to_owned()
-            )))
-        ]
-        .spacing(sizes::P * 2.0)
-        .align_items(Alignment::Center)
-        .into()
+            .push(horizontal_space(Length::Fill))
+            .push(actions)
+            .into()
     }
 }
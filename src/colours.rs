@@ -1,56 +1,150 @@
-use iced::Color;
+use iced::{Color, Theme};
 
-/// TODO: replace
-/// this is probably too harsh
-pub fn get_red() -> Color {
-    Color::from([1.0, 0.0, 0.0])
+/// The colours that every stylesheet and view draws from.
+///
+/// Each field is named for what it's used for rather than what colour it is,
+/// so that the light, dark and high contrast palettes can be swapped at runtime.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    /// behind the main content
+    pub background: Color,
+    /// text on top of `background`
+    pub text: Color,
+    /// labels and other less important text
+    pub secondary_text: Color,
+    /// headers, the sidebar and primary buttons
+    pub accent: Color,
+    /// text on top of `accent`
+    pub on_accent: Color,
+    /// secondary buttons and the active tab
+    pub secondary_accent: Color,
+    /// text on top of `secondary_accent`
+    pub on_secondary_accent: Color,
+    /// errors and destructive actions
+    pub danger: Color,
+    /// used to show that something is healthy
+    pub success: Color,
 }
 
-/// HERE IS A FUN COLOUR SCHEME
-/// I USED COOLORS
-/// <palette>
-///   <color name="Lavender blush" hex="fae8eb" r="250" g="232" b="235" />
-///   <color name="Tea rose (red)" hex="f6caca" r="246" g="202" b="202" />
-///   <color name="Cordovan" hex="8e3e48" r="142" g="62" b="72" />
-///   <color name="Lilac" hex="cd9fcc" r="205" g="159" b="204" />
-///   <color name="Federal blue" hex="0a014f" r="10" g="1" b="79" />
-/// </palette>
+impl Palette {
+    /// HERE IS A FUN COLOUR SCHEME
+    /// I USED COOLORS
+    /// <palette>
+    ///   <color name="Lavender blush" hex="fae8eb" r="250" g="232" b="235" />
+    ///   <color name="Tea rose (red)" hex="f6caca" r="246" g="202" b="202" />
+    ///   <color name="Cordovan" hex="8e3e48" r="142" g="62" b="72" />
+    ///   <color name="Lilac" hex="cd9fcc" r="205" g="159" b="204" />
+    ///   <color name="Federal blue" hex="0a014f" r="10" g="1" b="79" />
+    /// </palette>
+    pub const LIGHT: Palette = Palette {
+        // Lavender blush
+        background: rgb(250, 232, 235),
+        // Deep wine
+        text: rgb(22, 4, 13),
+        // Cordovan
+        secondary_text: rgb(169, 112, 118),
+        // Federal blue
+        accent: rgb(10, 1, 79),
+        on_accent: rgb(250, 232, 235),
+        // Lilac
+        secondary_accent: rgb(205, 159, 204),
+        on_secondary_accent: rgb(22, 4, 13),
+        danger: rgb(204, 51, 51),
+        success: rgb(77, 204, 102),
+    };
 
-/// Lavendar blush
-/// background and white text
-pub fn get_white() -> Color {
-    Color::from([250.0 / 255.0, 232.0 / 255.0, 235.0 / 255.0])
-}
+    /// The same colour scheme as `LIGHT`, with the background and text swapped around.
+    /// Federal blue is too close to the dark background for the header, so Cordovan is the accent instead.
+    pub const DARK: Palette = Palette {
+        background: rgb(30, 22, 36),
+        text: rgb(250, 232, 235),
+        secondary_text: rgb(205, 159, 204),
+        // Cordovan
+        accent: rgb(142, 62, 72),
+        on_accent: rgb(250, 232, 235),
+        // Lilac
+        secondary_accent: rgb(205, 159, 204),
+        on_secondary_accent: rgb(22, 4, 13),
+        danger: rgb(240, 96, 96),
+        success: rgb(77, 204, 102),
+    };
+
+    /// Pure black and white with saturated accents, for people who have trouble reading the other palettes
+    pub const HIGH_CONTRAST: Palette = Palette {
+        background: rgb(0, 0, 0),
+        text: rgb(255, 255, 255),
+        secondary_text: rgb(255, 255, 0),
+        accent: rgb(0, 0, 160),
+        on_accent: rgb(255, 255, 255),
+        secondary_accent: rgb(255, 255, 0),
+        on_secondary_accent: rgb(0, 0, 0),
+        danger: rgb(255, 64, 64),
+        success: rgb(0, 255, 0),
+    };
+
+    const ALL: [Palette; 3] = [Palette::LIGHT, Palette::DARK, Palette::HIGH_CONTRAST];
+
+    /// # Returns
+    /// An iced theme built from this palette, so that the widgets that aren't styled by this app
+    /// (text inputs, pick lists, scrollbars, ...) match the rest of the app
+    pub fn to_theme(self) -> Theme {
+        Theme::custom(self.iced_palette())
+    }
+
+    /// # Returns
+    /// The palette that `theme` was built from with `to_theme`, or the light palette if it wasn't built from one.
+    /// Stylesheets are only handed the iced theme, which only has some of the colours, so this finds the rest.
+    pub fn of(theme: &Theme) -> Palette {
+        let iced_palette = theme.palette();
+        Palette::ALL
+            .into_iter()
+            .find(|palette| palette.iced_palette() == iced_palette)
+            .unwrap_or_default()
+    }
 
-/// Federal blue
-/// strong accent
-pub fn get_blue() -> Color {
-    Color::from([10.0 / 255.0, 1.0 / 255.0, 79.0 / 255.0])
+    fn iced_palette(self) -> iced::theme::Palette {
+        iced::theme::Palette {
+            background: self.background,
+            text: self.text,
+            primary: self.accent,
+            success: self.success,
+            danger: self.danger,
+        }
+    }
 }
 
-/// Cordovan
-/// secondary text
-pub fn get_grey() -> Color {
-    Color::from([169.0 / 255.0, 112.0 / 255.0, 118.0 / 255.0])
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::LIGHT
+    }
 }
 
-/// Lilac
-/// Secondary accent
-pub fn get_lilac() -> Color {
-    Color::from([205.0 / 255.0, 159.0 / 255.0, 204.0 / 255.0])
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color {
+        r: r as f32 / 255.0,
+        g: g as f32 / 255.0,
+        b: b as f32 / 255.0,
+        a: 1.0,
+    }
 }
 
-/// Deep wine
-/// black text
-pub fn get_black() -> Color {
-    Color::from([22.0 / 255.0, 4.0 / 255.0, 13.0 / 255.0])
+/// mix the given colours.
+/// `amt` is how much of `other` ends up in the result.
+pub fn mix(colour: Color, other: Color, amt: f32) -> Color {
+    let inv = 1.0 - amt;
+    Color {
+        r: colour.r * inv + other.r * amt,
+        g: colour.g * inv + other.g * amt,
+        b: colour.b * inv + other.b * amt,
+        a: colour.a * inv + other.a * amt,
+    }
 }
 
 /// desaturate the given colour.
 /// probably a little wrong but works okay.
 /// based on https://stackoverflow.com/questions/70966873/algorithm-to-desaturate-rgb-color
 pub fn desaturate(colour: Color, amt: f32) -> Color {
-    if amt < 0.0 || amt >= 1.0 {
+    if !(0.0..1.0).contains(&amt) {
         panic!("desaturation amount must be 0 < amt < 1");
     }
     let lum = 0.3 * colour.r + 0.6 * colour.g + 0.11 * colour.b;
@@ -0,0 +1,109 @@
+//! A command line like the one in k9s, opened with `:`
+
+use std::str::FromStr;
+
+use iced::{
+    widget::{column, container, row, text, text_input},
+    Alignment, Element, Length, Padding,
+};
+
+use crate::{
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    messages::Message,
+    resource_type::ResourceType,
+    sizes,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteCommand {
+    /// Only lists one type of resource, or every type if `None`, e.g. `pods` or `all`
+    Resource(Option<ResourceType>),
+    /// Switches to the namespace, or asks for one if `None`, e.g. `ns kube-system`
+    Namespace(Option<String>),
+    /// Switches to the context, or asks for one if `None`, e.g. `ctx staging`
+    Context(Option<String>),
+}
+
+impl FromStr for PaletteCommand {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut words = input.trim().trim_start_matches(':').split_whitespace();
+        let Some(command) = words.next() else {
+            return Err(String::from(
+                "Type a command, e.g. pods, ns kube-system or ctx staging",
+            ));
+        };
+        let argument = words.next().map(str::to_owned);
+        if words.next().is_some() {
+            return Err(format!("{} takes at most one argument", command));
+        }
+        match command.to_lowercase().as_str() {
+            "ns" | "namespace" | "namespaces" => Ok(Self::Namespace(argument)),
+            "ctx" | "context" | "contexts" => Ok(Self::Context(argument)),
+            _ if argument.is_some() => Err(format!("{} doesn't take an argument", command)),
+            "all" => Ok(Self::Resource(None)),
+            name => ResourceType::from_str(name)
+                .map(|resource_type| Self::Resource(Some(resource_type)))
+                .map_err(|_error| format!("Unknown command: {}", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandPalette {
+    field_value: String,
+    /// Why the last command couldn't be run
+    error: Option<String>,
+}
+
+impl CommandPalette {
+    /// The id of the text field, so that it can be focused when the palette is opened
+    pub fn id() -> text_input::Id {
+        text_input::Id::new("command-palette")
+    }
+
+    pub fn set_field_value(&mut self, value: String) {
+        self.field_value = value;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn parse(&self) -> Result<PaletteCommand, String> {
+        self.field_value.parse()
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let mut content = column![row![
+            text(":").size(sizes::H2).style(palette.on_accent),
+            text_input(
+                "pods, deploy, sts, ds, rs, all, ns <namespace> or ctx <context>",
+                &self.field_value
+            )
+            .id(Self::id())
+            .on_input(Message::CommandPaletteChanged)
+            .on_submit(Message::CommandPaletteSubmitted),
+        ]
+        .spacing(sizes::SEP)
+        .align_items(Alignment::Center)]
+        .spacing(sizes::SMOL_SEP);
+        if let Some(error) = &self.error {
+            content = content.push(text(error).size(sizes::P).style(palette.danger));
+        }
+
+        container(content)
+            .width(Length::Fill)
+            .padding(Padding {
+                bottom: sizes::SEP,
+                top: sizes::SEP,
+                left: 2.0 * sizes::SEP,
+                right: 2.0 * sizes::SEP,
+            })
+            .style(as_container_theme(ContainerTheme::Dark))
+            .into()
+    }
+}
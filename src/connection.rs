@@ -0,0 +1,496 @@
+use std::path::PathBuf;
+
+use iced::{
+    widget::{button, column, container, horizontal_space, row, text, text_input, Row},
+    Alignment, Command, Element, Length, Padding,
+};
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    kube_context::{Impersonation, KubeContext, Safety},
+    kube_interface,
+    messages::{ImpersonationMessage, Message},
+    sizes,
+    state::AppState,
+    tab::{Tab, TabId},
+};
+
+/// Identifies a connection, so that messages from background work end up at the right connection
+pub type ConnectionId = usize;
+
+/// Whether the API server of a cluster responded the last time it was checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    Unknown,
+    Reachable,
+    Unreachable,
+}
+
+/// A context that the user connected to, along with the tabs that are open for it
+#[derive(Debug, Clone)]
+pub struct Connection {
+    id: ConnectionId,
+    context: KubeContext,
+    health: Health,
+    /// Set when the credentials were rejected, which pauses refreshing until the user re-authenticates
+    auth_expired: bool,
+    /// Set while the user is choosing who to impersonate
+    editing_impersonation: bool,
+    impersonate_user_field_value: String,
+    /// Comma separated
+    impersonate_groups_field_value: String,
+    tabs: Vec<Tab>,
+    active_tab: Option<TabId>,
+}
+
+impl Connection {
+    pub fn new(id: ConnectionId, context: KubeContext) -> Connection {
+        Connection {
+            id,
+            context,
+            health: Health::Unknown,
+            auth_expired: false,
+            editing_impersonation: false,
+            impersonate_user_field_value: String::from(""),
+            impersonate_groups_field_value: String::from(""),
+            tabs: vec![],
+            active_tab: None,
+        }
+    }
+
+    pub fn get_id(&self) -> ConnectionId {
+        self.id
+    }
+
+    pub fn get_context(&self) -> &KubeContext {
+        &self.context
+    }
+
+    pub fn get_health(&self) -> Health {
+        self.health
+    }
+
+    pub fn get_tab_mut(&mut self, id: TabId) -> Option<&mut Tab> {
+        self.tabs.iter_mut().find(|tab| tab.get_id() == id)
+    }
+
+    pub fn get_active_tab(&self) -> Option<TabId> {
+        self.active_tab
+    }
+
+    pub fn has_tab(&self, id: TabId) -> bool {
+        self.tabs.iter().any(|tab| tab.get_id() == id)
+    }
+
+    /// Opens the given tab after the other tabs, and switches to it
+    pub fn add_tab(&mut self, tab: Tab) {
+        self.active_tab = Some(tab.get_id());
+        self.tabs.push(tab);
+    }
+
+    /// Switches to the given tab
+    ///
+    /// # Returns
+    /// A command that refreshes the tab, since it might not have been refreshed in a while
+    pub fn select_tab(&mut self, id: TabId) -> Command<Message> {
+        self.active_tab = Some(id);
+        match self.get_tab_mut(id) {
+            Some(tab) => tab.reload(),
+            None => Command::none(),
+        }
+    }
+
+    pub fn close_tab(&mut self, id: TabId) {
+        let Some(index) = self.tabs.iter().position(|tab| tab.get_id() == id) else {
+            return;
+        };
+        self.tabs.remove(index);
+        if self.active_tab == Some(id) {
+            // switch to the neighbouring tab, like most tabbed interfaces do
+            self.active_tab = self
+                .tabs
+                .get(index.min(self.tabs.len().saturating_sub(1)))
+                .map(Tab::get_id);
+        }
+    }
+
+    /// Moves the given tab `offset` places to the right (or to the left if `offset` is negative)
+    pub fn move_tab(&mut self, id: TabId, offset: isize) {
+        let Some(index) = self.tabs.iter().position(|tab| tab.get_id() == id) else {
+            return;
+        };
+        let new_index = index.saturating_add_signed(offset).min(self.tabs.len() - 1);
+        let tab = self.tabs.remove(index);
+        self.tabs.insert(new_index, tab);
+    }
+
+    /// Refreshes the tabs of this connection.
+    ///
+    /// The active tab of the active connection is refreshed every time,
+    /// but the rest of the tabs are only refreshed when `include_background` is set,
+    /// so that the view the user is looking at gets priority.
+    /// A refresh that's still waiting for the cluster is cancelled and replaced,
+    /// so that a request that hangs can't stop a tab from refreshing.
+    pub fn reload(
+        &mut self,
+        is_active_connection: bool,
+        include_background: bool,
+    ) -> Command<Message> {
+        // every request would fail the same way until the user logs in again
+        if self.auth_expired {
+            return Command::none();
+        }
+        let active_tab = self.active_tab;
+        Command::batch(
+            self.tabs
+                .iter_mut()
+                .filter(|tab| {
+                    include_background || (is_active_connection && Some(tab.get_id()) == active_tab)
+                })
+                .map(Tab::reload),
+        )
+    }
+
+    pub fn check_health(&self) -> Command<Message> {
+        if self.auth_expired {
+            return Command::none();
+        }
+        let id = self.id;
+        Command::perform(
+            kube_interface::check_cluster_accessible(self.context.clone()),
+            move |res| Message::HealthChecked(id, res),
+        )
+    }
+
+    pub fn set_health(&mut self, health: Health) {
+        self.health = health;
+    }
+
+    pub fn is_auth_expired(&self) -> bool {
+        self.auth_expired
+    }
+
+    pub fn set_auth_expired(&mut self, auth_expired: bool) {
+        self.auth_expired = auth_expired;
+    }
+
+    /// Reloads the credentials from the kubeconfig, e.g. after the user logged in again
+    pub fn reauthenticate(&self, kubeconfig_files: Vec<PathBuf>) -> Command<Message> {
+        let id = self.id;
+        Command::perform(
+            kube_interface::reauthenticate(self.context.clone(), kubeconfig_files),
+            move |res| Message::Reauthenticated(id, res),
+        )
+    }
+
+    /// # Returns
+    /// Whether the impersonation of the context changed, in which case it should be saved
+    /// and the tabs reloaded, since what the user can see may have changed
+    pub fn update_impersonation(&mut self, message: ImpersonationMessage) -> bool {
+        match message {
+            ImpersonationMessage::EditRequested => {
+                let impersonation = self.context.get_impersonation().unwrap_or_default();
+                self.impersonate_user_field_value = impersonation.user;
+                self.impersonate_groups_field_value = impersonation.groups.join(", ");
+                self.editing_impersonation = true;
+                false
+            }
+            ImpersonationMessage::UserFieldChanged(value) => {
+                self.impersonate_user_field_value = value;
+                false
+            }
+            ImpersonationMessage::GroupsFieldChanged(value) => {
+                self.impersonate_groups_field_value = value;
+                false
+            }
+            ImpersonationMessage::Cancelled => {
+                self.editing_impersonation = false;
+                false
+            }
+            ImpersonationMessage::ApplyRequested => {
+                let user = self.impersonate_user_field_value.trim();
+                // the API server refuses to impersonate groups without a user
+                if user.is_empty() {
+                    return false;
+                }
+                let groups = self
+                    .impersonate_groups_field_value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|group| !group.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+                self.context.set_impersonation(Some(Impersonation {
+                    user: user.to_owned(),
+                    groups,
+                }));
+                self.editing_impersonation = false;
+                true
+            }
+            ImpersonationMessage::StopRequested => {
+                self.context.set_impersonation(None);
+                self.editing_impersonation = false;
+                true
+            }
+        }
+    }
+
+    pub fn get_tabs(&self) -> &[Tab] {
+        &self.tabs
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        state: &'a AppState,
+        unread_notifications: usize,
+    ) -> Element<'a, Message> {
+        let palette = state.settings.theme.palette();
+        let content = match self
+            .tabs
+            .iter()
+            .find(|tab| Some(tab.get_id()) == self.active_tab)
+        {
+            Some(tab) => tab.view(state, unread_notifications),
+            None => container(text("No tabs are open").style(palette.text))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .style(as_container_theme(ContainerTheme::Light))
+                .into(),
+        };
+
+        let mut view = column![self.tab_bar()];
+        if self.auth_expired {
+            view = view.push(self.reauthenticate_banner(palette));
+        }
+        if self.editing_impersonation {
+            view = view.push(self.impersonation_form(palette));
+        }
+        if self.context.get_safety() == Safety::Protected {
+            view = view.push(self.protected_banner(palette));
+        }
+        view.push(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    /// Asks the user to log in again, since nothing will load until they do
+    fn reauthenticate_banner(&self, palette: Palette) -> Element<'_, Message> {
+        container(
+            row![
+                text(format!(
+                    "Your credentials for {} have expired or were rejected. \
+                    Log in again the way you usually do, e.g. with your cloud provider's CLI, then re-authenticate. \
+                    Refreshing is paused until then.",
+                    self.context.get_name()
+                ))
+                .style(palette.danger)
+                .width(Length::Fill),
+                button(container(text("Re-authenticate")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(Message::ReauthenticateRequested(self.id))
+                .style(as_button_theme(ButtonTheme::Primary)),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(sizes::SEP)
+        .style(as_container_theme(ContainerTheme::Light))
+        .into()
+    }
+
+    /// Makes it obvious that changes made here matter, e.g. in production
+    fn protected_banner(&self, palette: Palette) -> Element<'_, Message> {
+        container(
+            text(format!(
+                "{} is a protected context. Changes have to be confirmed by typing the name of the resource.",
+                self.context.get_name()
+            ))
+            .style(palette.on_accent),
+        )
+        .width(Length::Fill)
+        .padding(sizes::SEP)
+        .style(as_container_theme(ContainerTheme::Danger))
+        .into()
+    }
+
+    /// Picks who requests are sent on behalf of, like `kubectl --as` and `--as-group` do
+    fn impersonation_form(&self, palette: Palette) -> Element<'_, Message> {
+        let id = self.id;
+        let mut apply_button = button(container(text("Impersonate")).padding(Padding {
+            bottom: 0.0,
+            top: 0.0,
+            left: sizes::SEP,
+            right: sizes::SEP,
+        }))
+        .style(as_button_theme(ButtonTheme::Primary));
+        if !self.impersonate_user_field_value.trim().is_empty() {
+            apply_button = apply_button.on_press(Message::ImpersonationMessage(
+                id,
+                ImpersonationMessage::ApplyRequested,
+            ));
+        }
+        let mut form = row![
+            text("Impersonate").style(palette.text),
+            text_input(
+                "User, e.g. jane or system:serviceaccount:ns:name",
+                &self.impersonate_user_field_value
+            )
+            .on_input(move |value| {
+                Message::ImpersonationMessage(id, ImpersonationMessage::UserFieldChanged(value))
+            })
+            .on_submit(Message::ImpersonationMessage(
+                id,
+                ImpersonationMessage::ApplyRequested
+            )),
+            text_input(
+                "Groups, separated by commas (optional)",
+                &self.impersonate_groups_field_value
+            )
+            .on_input(move |value| {
+                Message::ImpersonationMessage(id, ImpersonationMessage::GroupsFieldChanged(value))
+            })
+            .on_submit(Message::ImpersonationMessage(
+                id,
+                ImpersonationMessage::ApplyRequested
+            )),
+            apply_button,
+        ]
+        .spacing(sizes::SEP)
+        .align_items(Alignment::Center);
+        if self.context.get_impersonation().is_some() {
+            form = form.push(
+                button(container(text("Stop Impersonating")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(Message::ImpersonationMessage(
+                    id,
+                    ImpersonationMessage::StopRequested,
+                ))
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            );
+        }
+        form = form.push(
+            button(container(text("Cancel")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }))
+            .on_press(Message::ImpersonationMessage(
+                id,
+                ImpersonationMessage::Cancelled,
+            ))
+            .style(as_button_theme(ButtonTheme::Secondary)),
+        );
+
+        container(form)
+            .width(Length::Fill)
+            .padding(sizes::SEP)
+            .style(as_container_theme(ContainerTheme::Light))
+            .into()
+    }
+
+    fn tab_bar(&self) -> Element<'_, Message> {
+        let tabs: Vec<Element<Message>> = self
+            .tabs
+            .iter()
+            .map(|tab| {
+                let active = Some(tab.get_id()) == self.active_tab;
+                let mut tab_row = Row::new().align_items(Alignment::Center);
+                if active {
+                    tab_row = tab_row.push(
+                        button(text("‹").size(sizes::P))
+                            .on_press(Message::TabMoved(tab.get_id(), -1))
+                            .padding(sizes::SMOL_SEP)
+                            .style(as_button_theme(ButtonTheme::Secondary)),
+                    );
+                }
+                tab_row = tab_row.push(
+                    button(text(tab.title()).size(sizes::P))
+                        .on_press(Message::TabSelected(tab.get_id()))
+                        .padding(sizes::SMOL_SEP)
+                        .style(as_button_theme(if active {
+                            ButtonTheme::Secondary
+                        } else {
+                            ButtonTheme::Ghost
+                        })),
+                );
+                if active {
+                    tab_row = tab_row.push(
+                        button(text("›").size(sizes::P))
+                            .on_press(Message::TabMoved(tab.get_id(), 1))
+                            .padding(sizes::SMOL_SEP)
+                            .style(as_button_theme(ButtonTheme::Secondary)),
+                    );
+                }
+                tab_row
+                    .push(
+                        button(text("×").size(sizes::P))
+                            .on_press(Message::TabClosed(tab.get_id()))
+                            .padding(sizes::SMOL_SEP)
+                            .style(as_button_theme(if active {
+                                ButtonTheme::Secondary
+                            } else {
+                                ButtonTheme::Ghost
+                            })),
+                    )
+                    .into()
+            })
+            .collect();
+
+        container(
+            row![
+                Row::with_children(tabs)
+                    .spacing(sizes::SEP)
+                    .align_items(Alignment::Center),
+                button(text("+").size(sizes::P))
+                    .on_press(Message::NewTabRequested(self.id))
+                    .padding(sizes::SMOL_SEP)
+                    .style(as_button_theme(ButtonTheme::Ghost)),
+                horizontal_space(Length::Fill),
+                button(
+                    text(if self.context.is_read_only() {
+                        "Read-only: On"
+                    } else {
+                        "Read-only: Off"
+                    })
+                    .size(sizes::P)
+                )
+                .on_press(Message::ReadOnlyToggled(self.id))
+                .padding(sizes::SMOL_SEP)
+                .style(as_button_theme(ButtonTheme::Ghost)),
+                button(text("Impersonate...").size(sizes::P))
+                    .on_press(Message::ImpersonationMessage(
+                        self.id,
+                        ImpersonationMessage::EditRequested
+                    ))
+                    .padding(sizes::SMOL_SEP)
+                    .style(as_button_theme(ButtonTheme::Ghost)),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(Padding {
+            bottom: 0.0,
+            top: sizes::SEP,
+            left: sizes::SEP,
+            right: sizes::SEP,
+        })
+        .style(as_container_theme(ContainerTheme::Dark))
+        .into()
+    }
+}
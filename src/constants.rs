@@ -1,4 +1,13 @@
-/// Constants that control how the app functions
+//! Constants that control how the app functions
 
 pub const TOAST_TIMEOUT: u64 = 10;
 pub const CLUSTER_REFRESH: u64 = 4;
+pub const HEALTH_CHECK: u64 = 15;
+/// Tabs that the user isn't looking at are only refreshed once every this many refreshes
+pub const BACKGROUND_REFRESH_TICKS: u64 = 4;
+/// How many seconds the permissions of the user in a namespace are remembered for
+pub const PERMISSION_CACHE: u64 = 60;
+/// How many objects a batch action changes at the same time
+pub const BATCH_CONCURRENCY: usize = 5;
+/// How many notifications the notification centre keeps, dropping the oldest ones first
+pub const NOTIFICATION_HISTORY: usize = 200;
@@ -3,22 +3,31 @@ use iced::{
     Theme,
 };
 
-use crate::colours;
+use crate::colours::Palette;
 
 pub enum ContainerTheme {
     Light,
     Dark,
+    /// For warnings that shouldn't be missed
+    Danger,
+    /// The row that keyboard shortcuts act on
+    Highlighted,
 }
 
 impl iced::widget::container::StyleSheet for ContainerTheme {
     type Style = Theme;
 
-    fn appearance(&self, _theme: &Theme) -> container::Appearance {
+    fn appearance(&self, theme: &Theme) -> container::Appearance {
+        let palette = Palette::of(theme);
+        let (background, text_color) = match self {
+            ContainerTheme::Dark => (palette.accent, palette.on_accent),
+            ContainerTheme::Light => (palette.background, palette.text),
+            ContainerTheme::Danger => (palette.danger, palette.on_accent),
+            ContainerTheme::Highlighted => (palette.secondary_accent, palette.on_secondary_accent),
+        };
         container::Appearance {
-            background: Some(match self {
-                ContainerTheme::Dark => colours::get_blue().into(),
-                ContainerTheme::Light => colours::get_white().into(),
-            }),
+            background: Some(background.into()),
+            text_color: Some(text_color),
             ..Default::default()
         }
     }
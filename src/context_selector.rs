@@ -1,13 +1,16 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use crate::button_theme::as_button_theme;
 use crate::button_theme::ButtonTheme;
-use crate::circular_loading_theme::CircularLoadingTheme;
 use crate::circular_loading_theme::as_circular_theme;
-use crate::colours;
+use crate::circular_loading_theme::CircularLoadingTheme;
+use crate::colours::Palette;
 use crate::container_theme::as_container_theme;
 use crate::container_theme::ContainerTheme;
-use crate::custom_widgets::circular_loading_spinner;
 use crate::custom_widgets::circular_loading_spinner::Circular;
 use crate::kube_interface;
+use crate::kubeconfig::{self, ContextEntry, MergedKubeconfig};
 use crate::messages::ContextSelectorMessage;
 use crate::messages::Message;
 use crate::sizes;
@@ -15,51 +18,157 @@ use crate::sizes::H2;
 use iced::widget::container;
 use iced::widget::horizontal_space;
 use iced::widget::row;
-use iced::widget::{
-    button, column, combo_box as combo_box_fun,
-    combo_box::{self},
-    text,
-};
+use iced::widget::{button, column, scrollable, text, text_input, Column};
+use iced::Alignment;
 use iced::Length;
 use iced::Padding;
 use iced::{Command, Element};
+use kube::config::Kubeconfig;
+
+/// Whether the cluster of a context could be reached
+#[derive(Debug, Clone)]
+enum Probe {
+    /// Waits for the user to ask, since the context runs a command to get its credentials,
+    /// which can be slow or open a browser to log in
+    OnDemand,
+    Pending,
+    /// Holds the version of Kubernetes the cluster is running
+    Reachable(String),
+    Unreachable,
+}
 
 #[derive(Debug, Clone)]
 pub struct ContextSelector {
-    state: combo_box::State<String>,
+    contexts: Vec<ContextEntry>,
+    /// Every kubeconfig file merged into one, which each context is probed with
+    kubeconfig: Kubeconfig,
+    probes: HashMap<String, Probe>,
+    /// Only the contexts whose name or server contain this are listed
+    filter_value: String,
+    /// Name of the selected context
     selection: Option<String>,
     loading: bool,
+    /// Whether there is already a connected context to go back to
+    cancellable: bool,
+    /// Every kubeconfig file that's read, in order of priority
+    kubeconfig_files: Vec<PathBuf>,
+    /// Kubeconfig files that the user added on top of the ones in `$KUBECONFIG`
+    extra_kubeconfigs: Vec<PathBuf>,
+    kubeconfig_field_value: String,
 }
 
 impl ContextSelector {
-    pub fn new(contexts: Vec<String>) -> ContextSelector {
+    pub fn new(
+        merged: MergedKubeconfig,
+        cancellable: bool,
+        kubeconfig_files: Vec<PathBuf>,
+        extra_kubeconfigs: Vec<PathBuf>,
+    ) -> ContextSelector {
+        let contexts = merged.contexts;
         ContextSelector {
-            state: combo_box::State::new(contexts),
-            selection: None,
+            probes: contexts
+                .iter()
+                .map(|context| {
+                    let probe = if context.runs_command {
+                        Probe::OnDemand
+                    } else {
+                        Probe::Pending
+                    };
+                    (context.name.clone(), probe)
+                })
+                .collect(),
+            selection: contexts
+                .iter()
+                .find(|context| context.is_current)
+                .map(|context| context.name.clone()),
+            contexts,
+            kubeconfig: merged.kubeconfig,
+            filter_value: String::from(""),
             loading: false,
+            cancellable,
+            kubeconfig_files,
+            extra_kubeconfigs,
+            kubeconfig_field_value: String::from(""),
+        }
+    }
+
+    /// Checks whether the cluster of each context can be reached, in the background.
+    /// Contexts that run a command to get their credentials are left until the user asks.
+    pub fn probe_all(&self) -> Command<Message> {
+        Command::batch(
+            self.probes
+                .iter()
+                .filter(|(_, probe)| matches!(probe, Probe::Pending))
+                .map(|(name, _)| self.probe(name.clone())),
+        )
+    }
+
+    fn probe(&self, name: String) -> Command<Message> {
+        Command::perform(
+            kube_interface::probe_context(name.clone(), self.kubeconfig.clone()),
+            move |res| ContextSelectorMessage::Probed(name, res).into(),
+        )
+    }
+
+    /// Marks the named context as the `current-context` of the kubeconfig, after it was written
+    pub fn set_current(&mut self, name: &str) {
+        for context in self.contexts.iter_mut() {
+            context.is_current = context.name == name;
         }
     }
 
     pub fn update(&mut self, message: ContextSelectorMessage) -> Command<Message> {
         match message {
-            ContextSelectorMessage::DropDownItemSelected(kube_ctx) => {
-                self.selection = Some(kube_ctx);
-                self.state.unfocus();
+            ContextSelectorMessage::FilterChanged(value) => {
+                self.filter_value = value;
+
+                Command::none()
+            }
+            ContextSelectorMessage::EntrySelected(name) => {
+                self.selection = Some(name);
 
                 Command::none()
             }
-            ContextSelectorMessage::DropDownClosed => Command::none(),
             ContextSelectorMessage::ContextSelected(kube_ctx_name) => {
                 self.loading = true;
                 Command::perform(
-                    kube_interface::load_named_context(kube_ctx_name),
+                    kube_interface::load_named_context(
+                        kube_ctx_name,
+                        self.kubeconfig_files.clone(),
+                    ),
                     Message::ContextLoaded,
                 )
             }
+            ContextSelectorMessage::ProbeRequested(name) => {
+                self.probes.insert(name.clone(), Probe::Pending);
+
+                self.probe(name)
+            }
+            ContextSelectorMessage::Probed(name, res) => {
+                let probe = match res {
+                    Ok(version) => Probe::Reachable(version),
+                    Err(..) => Probe::Unreachable,
+                };
+                self.probes.insert(name, probe);
+
+                Command::none()
+            }
+            ContextSelectorMessage::KubeconfigFieldChanged(value) => {
+                self.kubeconfig_field_value = value;
+
+                Command::none()
+            }
+            ContextSelectorMessage::AddKubeconfigRequested => {
+                if self.kubeconfig_field_value.trim().is_empty() {
+                    return Command::none();
+                }
+                let path = kubeconfig::expand_home(self.kubeconfig_field_value.trim());
+                Command::perform(kubeconfig::check_file(path), Message::KubeconfigAdded)
+            }
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
         let mut set_context_button = button(container(text("Set Context")).padding(Padding {
             bottom: 0.0,
             top: 0.0,
@@ -72,35 +181,88 @@ impl ContextSelector {
             None => set_context_button,
         };
 
+        // only writes to the kubeconfig when the user explicitly asks for it
+        let mut make_current_button = button(
+            container(text("Make Current in Kubeconfig")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }),
+        )
+        .style(as_button_theme(ButtonTheme::Secondary));
+        if let Some(selection) = self.selection.as_ref() {
+            make_current_button = make_current_button
+                .on_press(Message::KubeconfigContextRequested(selection.clone()));
+        }
+
         let loading: Element<Message> = container(if self.loading {
-            Into::<Element<Message>>::into(Circular::new().style(as_circular_theme(CircularLoadingTheme::Primary)))
+            Into::<Element<Message>>::into(
+                Circular::new().style(as_circular_theme(CircularLoadingTheme::Primary)),
+            )
         } else {
             text("").into()
-        }).width(Length::Fill).align_x(iced::alignment::Horizontal::Center).into();
+        })
+        .width(Length::Fill)
+        .align_x(iced::alignment::Horizontal::Center)
+        .into();
+
+        let cancel_button: Element<Message> = if self.cancellable {
+            button(container(text("Cancel")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }))
+            .on_press(Message::ChangeContextCancelled)
+            .style(as_button_theme(ButtonTheme::Secondary))
+            .into()
+        } else {
+            horizontal_space(0).into()
+        };
+
+        let filter = self.filter_value.to_lowercase();
+        let entries: Vec<Element<Message>> = self
+            .contexts
+            .iter()
+            .filter(|context| {
+                context.name.to_lowercase().contains(&filter)
+                    || context
+                        .server
+                        .as_ref()
+                        .is_some_and(|server| server.to_lowercase().contains(&filter))
+            })
+            .map(|context| self.entry(context, palette))
+            .collect();
 
         container(
             column![
                 text("Pick a new context to use")
                     .size(H2)
-                    .style(colours::get_black()),
-                combo_box_fun(
-                    &self.state,
-                    "New context to view",
-                    self.selection.as_ref(),
-                    |selection| Message::ContextSelectorMessage(
-                        ContextSelectorMessage::DropDownItemSelected(selection)
-                    )
-                )
-                .on_close(Message::ContextSelectorMessage(
-                    ContextSelectorMessage::DropDownClosed
-                )),
+                    .style(palette.text),
+                text_input("Filter contexts", &self.filter_value)
+                    .on_input(|value| ContextSelectorMessage::FilterChanged(value).into()),
+                scrollable(Column::with_children(entries).spacing(sizes::SMOL_SEP))
+                    .height(Length::Fill),
                 row![
+                    cancel_button,
+                    button(container(text("Manage Kubeconfig")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .on_press(Message::KubeconfigEditorRequested)
+                    .style(as_button_theme(ButtonTheme::Secondary)),
                     horizontal_space(Length::Fill),
+                    make_current_button,
                     set_context_button.style(as_button_theme(ButtonTheme::Primary))
-                ],
+                ]
+                .spacing(sizes::SEP),
                 loading,
+                self.kubeconfigs_view(palette),
             ]
-            .max_width(400)
+            .max_width(800)
             .spacing(sizes::SEP),
         )
         .style(as_container_theme(ContainerTheme::Light))
@@ -110,4 +272,120 @@ impl ContextSelector {
         .center_x()
         .into()
     }
+
+    /// Shows where the context points to, and whether it can be reached
+    fn entry<'a>(&'a self, context: &'a ContextEntry, palette: Palette) -> Element<'a, Message> {
+        let selected = self.selection.as_ref() == Some(&context.name);
+        let secondary_text = if selected {
+            palette.on_secondary_accent
+        } else {
+            palette.secondary_text
+        };
+
+        let probe: Element<Message> = match self.probes.get(&context.name) {
+            Some(Probe::Reachable(version)) => row![
+                text("●").size(sizes::P).style(palette.success),
+                text(version).size(sizes::P).style(secondary_text),
+            ]
+            .spacing(sizes::SMOL_SEP)
+            .align_items(Alignment::Center)
+            .into(),
+            Some(Probe::Unreachable) => row![
+                text("●").size(sizes::P).style(palette.danger),
+                text("Unreachable").size(sizes::P).style(secondary_text),
+            ]
+            .spacing(sizes::SMOL_SEP)
+            .align_items(Alignment::Center)
+            .into(),
+            Some(Probe::OnDemand) => button(text("Check").size(sizes::P))
+                .on_press(ContextSelectorMessage::ProbeRequested(context.name.clone()).into())
+                .padding(sizes::SMOL_SEP)
+                .style(as_button_theme(ButtonTheme::Secondary))
+                .into(),
+            Some(Probe::Pending) | None => Circular::new()
+                .size(sizes::P)
+                .bar_height(2.0)
+                .style(as_circular_theme(CircularLoadingTheme::Primary))
+                .into(),
+        };
+
+        let name = if context.is_current {
+            format!("{} (current)", context.name)
+        } else {
+            context.name.clone()
+        };
+        let details = format!(
+            "{}  ·  user: {}  ·  namespace: {}",
+            context.server.as_deref().unwrap_or("unknown server"),
+            context.user.as_deref().unwrap_or("none"),
+            context.namespace.as_deref().unwrap_or("default"),
+        );
+
+        button(
+            row![
+                column![
+                    text(name),
+                    text(details)
+                        .size(sizes::P * 3.0 / 4.0)
+                        .style(secondary_text),
+                    text(context.source.display().to_string())
+                        .size(sizes::P * 3.0 / 4.0)
+                        .style(secondary_text),
+                ]
+                .width(Length::Fill),
+                probe,
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .on_press(ContextSelectorMessage::EntrySelected(context.name.clone()).into())
+        .style(if selected {
+            as_button_theme(ButtonTheme::Secondary)
+        } else {
+            iced::theme::Button::Text
+        })
+        .into()
+    }
+
+    /// Lists the kubeconfig files that the user added, and lets them add more
+    fn kubeconfigs_view(&self, palette: Palette) -> Element<'_, Message> {
+        let extra_kubeconfigs: Vec<Element<Message>> = self
+            .extra_kubeconfigs
+            .iter()
+            .map(|path| {
+                row![
+                    text(path.display().to_string()).width(Length::Fill),
+                    button(text("×").size(sizes::P))
+                        .on_press(Message::KubeconfigRemoved(path.clone()))
+                        .padding(sizes::SMOL_SEP)
+                        .style(as_button_theme(ButtonTheme::Secondary)),
+                ]
+                .align_items(Alignment::Center)
+                .into()
+            })
+            .collect();
+
+        column![
+            text("Extra kubeconfig files").style(palette.secondary_text),
+            Column::with_children(extra_kubeconfigs).spacing(sizes::SMOL_SEP),
+            row![
+                text_input("Path to a kubeconfig file", &self.kubeconfig_field_value)
+                    .on_input(|value| ContextSelectorMessage::KubeconfigFieldChanged(value).into())
+                    .on_submit(ContextSelectorMessage::AddKubeconfigRequested.into()),
+                button(container(text("Add")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(ContextSelectorMessage::AddKubeconfigRequested.into())
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        ]
+        .spacing(sizes::SEP)
+        .into()
+    }
 }
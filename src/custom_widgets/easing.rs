@@ -6,43 +6,12 @@ use lyon_algorithms::measure::PathMeasurements;
 use lyon_algorithms::path::{builder::NoAttributes, path::BuilderImpl, Path};
 use once_cell::sync::Lazy;
 
-pub static EMPHASIZED: Lazy<Easing> = Lazy::new(|| {
-    Easing::builder()
-        .cubic_bezier_to([0.05, 0.0], [0.133333, 0.06], [0.166666, 0.4])
-        .cubic_bezier_to([0.208333, 0.82], [0.25, 1.0], [1.0, 1.0])
-        .build()
-});
-
-pub static EMPHASIZED_DECELERATE: Lazy<Easing> = Lazy::new(|| {
-    Easing::builder()
-        .cubic_bezier_to([0.05, 0.7], [0.1, 1.0], [1.0, 1.0])
-        .build()
-});
-
-pub static EMPHASIZED_ACCELERATE: Lazy<Easing> = Lazy::new(|| {
-    Easing::builder()
-        .cubic_bezier_to([0.3, 0.0], [0.8, 0.15], [1.0, 1.0])
-        .build()
-});
-
 pub static STANDARD: Lazy<Easing> = Lazy::new(|| {
     Easing::builder()
         .cubic_bezier_to([0.2, 0.0], [0.0, 1.0], [1.0, 1.0])
         .build()
 });
 
-pub static STANDARD_DECELERATE: Lazy<Easing> = Lazy::new(|| {
-    Easing::builder()
-        .cubic_bezier_to([0.0, 0.0], [0.0, 1.0], [1.0, 1.0])
-        .build()
-});
-
-pub static STANDARD_ACCELERATE: Lazy<Easing> = Lazy::new(|| {
-    Easing::builder()
-        .cubic_bezier_to([0.3, 0.0], [1.0, 1.0], [1.0, 1.0])
-        .build()
-});
-
 pub struct Easing {
     path: Path,
     measurements: PathMeasurements,
@@ -113,7 +82,7 @@ impl Builder {
 
     fn point(p: impl Into<Point>) -> lyon_algorithms::geom::Point<f32> {
         let p: Point = p.into();
-        lyon_algorithms::geom::point(p.x.min(1.0).max(0.0), p.y.min(1.0).max(0.0))
+        lyon_algorithms::geom::point(p.x.clamp(0.0, 1.0), p.y.clamp(0.0, 1.0))
     }
 }
 
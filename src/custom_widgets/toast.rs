@@ -5,6 +5,8 @@
 //  * improve the styling of the toasts:
 //     * wider
 //     * change the background colour
+//  * optional technical details that can be expanded and copied
+//  * an optional action button, e.g. to undo what the toast is about
 
 use std::fmt;
 use std::time::{Duration, Instant};
@@ -17,13 +19,16 @@ use iced::advanced::{Clipboard, Shell, Widget};
 use iced::event::{self, Event};
 use iced::font::{Family, Weight};
 use iced::theme;
-use iced::widget::{button, column, container, horizontal_rule, horizontal_space, row, text};
+use iced::widget::{button, column, container, horizontal_space, row, text};
 use iced::window;
 use iced::{advanced, alignment};
 use iced::{mouse, Color, Font};
 use iced::{Alignment, Element, Length, Point, Rectangle, Renderer, Size, Theme, Vector};
 
-use crate::{colours, sizes};
+use crate::{
+    colours::{self, Palette},
+    messages, sizes,
+};
 
 pub const DEFAULT_TIMEOUT: u64 = 5;
 
@@ -40,40 +45,52 @@ impl Status {
     pub const ALL: &[Self] = &[Self::Primary, Self::Secondary, Self::Success, Self::Danger];
 }
 
+impl Status {
+    /// # Returns
+    /// The colour from the palette that stands for this status
+    pub fn colour(&self, palette: &Palette) -> Color {
+        match self {
+            Status::Primary => palette.accent,
+            Status::Secondary => palette.secondary_accent,
+            Status::Success => palette.success,
+            Status::Danger => palette.danger,
+        }
+    }
+
+    /// # Returns
+    /// The colour of text about something with this status, e.g. the title and body of a toast
+    pub fn text_colour(&self, palette: &Palette) -> Color {
+        // the status colour on its own can be too close to the tinted background, e.g. the secondary accent
+        // in the dark palette, so it's pulled towards the text colour
+        colours::mix(
+            palette.text,
+            colours::desaturate(self.colour(palette), 0.5),
+            0.5,
+        )
+    }
+}
+
 impl container::StyleSheet for Status {
     type Style = Theme;
 
     fn appearance(&self, theme: &Theme) -> container::Appearance {
-        let palette = theme.extended_palette();
-
-        let pair = match self {
-            Status::Primary => palette.primary.weak,
-            Status::Secondary => palette.secondary.weak,
-            Status::Success => palette.success.weak,
-            Status::Danger => palette.danger.weak,
-        };
-
-        let transparent_background = Color {
-            a: 0.9,
-            ..pair.color
-        };
+        let palette = Palette::of(theme);
+
+        // tint the background with the status colour, but keep it close enough to the
+        // background colour that the text stays readable in every palette
+        let tint = colours::mix(
+            palette.background,
+            colours::desaturate(self.colour(&palette), 0.5),
+            0.25,
+        );
+        let transparent_background = Color { a: 0.95, ..tint };
 
         container::Appearance {
             background: Some(transparent_background.into()),
-            text_color: pair.text.into(),
+            text_color: Some(palette.text),
             border_radius: sizes::BORDER_RADIUS.into(),
-            ..Default::default()
-        }
-    }
-}
-
-impl Into<iced::theme::Text> for Status {
-    fn into(self) -> iced::theme::Text {
-        match self {
-            Status::Danger => colours::desaturate(colours::get_red(), 0.5).into(),
-            Status::Primary => todo!(),
-            Status::Secondary => todo!(),
-            Status::Success => todo!(),
+            border_width: 2.0,
+            border_color: self.colour(&palette),
         }
     }
 }
@@ -95,6 +112,46 @@ pub struct Toast {
     pub title: String,
     pub body: String,
     pub status: Status,
+    /// Technical details that are hidden until the user asks for them
+    pub details: Option<String>,
+    /// Whether the details are being shown
+    pub expanded: bool,
+    pub action: Option<ToastAction>,
+}
+
+/// A button on a toast, and the message that's sent when it's pressed
+#[derive(Debug, Clone)]
+pub struct ToastAction {
+    pub label: String,
+    pub message: Box<messages::Message>,
+}
+
+impl Toast {
+    pub fn new(status: Status, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Toast {
+            title: title.into(),
+            body: body.into(),
+            status,
+            ..Default::default()
+        }
+    }
+
+    pub fn details(self, details: impl Into<String>) -> Self {
+        Toast {
+            details: Some(details.into()),
+            ..self
+        }
+    }
+
+    pub fn action(self, label: impl Into<String>, message: messages::Message) -> Self {
+        Toast {
+            action: Some(ToastAction {
+                label: label.into(),
+                message: Box::new(message),
+            }),
+            ..self
+        }
+    }
 }
 
 pub struct Manager<'a, Message> {
@@ -111,17 +168,61 @@ where
     pub fn new(
         content: impl Into<Element<'a, Message>>,
         toasts: &'a [Toast],
+        palette: Palette,
         on_close: impl Fn(usize) -> Message + 'a,
+        on_toggle_details: impl Fn(usize) -> Message + 'a,
+        on_copy_details: impl Fn(usize) -> Message + 'a,
+        on_action: impl Fn(usize) -> Message + 'a,
     ) -> Self {
         let toasts = toasts
             .iter()
             .enumerate()
             .map(|(index, toast)| {
+                let details: Element<'a, Message> = match &toast.details {
+                    Some(details) => {
+                        let toggle_label = if toast.expanded {
+                            "Hide details"
+                        } else {
+                            "Show details"
+                        };
+                        let mut details_column = column![row![
+                            button(text(toggle_label).size(sizes::P))
+                                .on_press((on_toggle_details)(index))
+                                .padding(sizes::SMOL_SEP)
+                                .style(theme::Button::Text),
+                            horizontal_space(Length::Fill),
+                            button(text("Copy").size(sizes::P))
+                                .on_press((on_copy_details)(index))
+                                .padding(sizes::SMOL_SEP)
+                                .style(theme::Button::Secondary),
+                        ]
+                        .align_items(Alignment::Center)]
+                        .spacing(sizes::SMOL_SEP);
+                        if toast.expanded {
+                            details_column = details_column.push(
+                                text(details.as_str())
+                                    .font(Font::MONOSPACE)
+                                    .size(sizes::P * 3.0 / 4.0),
+                            );
+                        }
+                        details_column.into()
+                    }
+                    None => column![].into(),
+                };
+                let action: Element<'a, Message> = match &toast.action {
+                    Some(action) => button(text(action.label.as_str()).size(sizes::P))
+                        .on_press((on_action)(index))
+                        .padding(sizes::SMOL_SEP)
+                        .style(theme::Button::Primary)
+                        .into(),
+                    None => column![].into(),
+                };
+
                 container(column![
                     container(
                         row![
                             text(toast.title.as_str())
-                                .style(toast.status)
+                                .style(toast.status.text_colour(&palette))
                                 .font(Font {
                                     family: Family::Name("Droid Sans"),
                                     weight: Weight::Bold,
@@ -130,7 +231,7 @@ where
                                 .size(sizes::P),
                             horizontal_space(Length::Fill),
                             button(
-                                container(text("×").size(sizes::P).style(colours::get_black()))
+                                container(text("×").size(sizes::P))
                                     .align_x(alignment::Horizontal::Center)
                                     .align_y(alignment::Vertical::Center)
                                     .width(sizes::P * 3.0 / 2.0)
@@ -144,9 +245,18 @@ where
                     )
                     .width(Length::Fill)
                     .padding(sizes::SEP),
-                    container(text(toast.body.as_str()).style(toast.status).size(sizes::P))
-                        .width(Length::Fill)
-                        .padding(sizes::SEP),
+                    container(
+                        column![
+                            text(toast.body.as_str())
+                                .style(toast.status.text_colour(&palette))
+                                .size(sizes::P),
+                            action,
+                            details
+                        ]
+                        .spacing(sizes::SMOL_SEP)
+                    )
+                    .width(Length::Fill)
+                    .padding(sizes::SEP),
                 ])
                 .style(theme::Container::Custom(Box::new(toast.status)))
                 .max_width(500)
@@ -184,7 +294,7 @@ impl<'a, Message> Widget<Message, Renderer> for Manager<'a, Message> {
     }
 
     fn tag(&self) -> widget::tree::Tag {
-        struct Marker(Vec<Instant>);
+        struct Marker;
         widget::tree::Tag::of::<Marker>()
     }
 
@@ -211,7 +321,7 @@ impl<'a, Message> Widget<Message, Renderer> for Manager<'a, Message> {
                 instants.truncate(new);
             }
             (old, new) if old < new => {
-                instants.extend(std::iter::repeat(Some(Instant::now())).take(new - old));
+                instants.extend(std::iter::repeat_n(Some(Instant::now()), new - old));
             }
             _ => {}
         }
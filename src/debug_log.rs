@@ -0,0 +1,66 @@
+use iced::{
+    widget::{button, column, container, horizontal_space, row, scrollable, text},
+    Alignment, Element, Font, Length, Padding,
+};
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    logging,
+    messages::Message,
+    sizes,
+};
+
+/// Shows the most recent lines of the log, so that they can be copied into a bug report
+pub fn view(palette: Palette) -> Element<'static, Message> {
+    let lines = logging::recent_lines();
+    let log: Element<Message> = if lines.is_empty() {
+        text("Nothing has been logged yet")
+            .style(palette.secondary_text)
+            .into()
+    } else {
+        scrollable(
+            text(lines.join("\n"))
+                .font(Font::MONOSPACE)
+                .size(sizes::P * 3.0 / 4.0),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    };
+
+    container(
+        column![
+            row![
+                text("Debug Log").size(sizes::H2),
+                horizontal_space(Length::Fill),
+                button(container(text("Copy")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(Message::DebugLogCopyRequested)
+                .style(as_button_theme(ButtonTheme::Secondary)),
+                button(container(text("Close")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(Message::DebugLogClosed)
+                .style(as_button_theme(ButtonTheme::Primary)),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+            log,
+        ]
+        .spacing(sizes::SEP),
+    )
+    .style(as_container_theme(ContainerTheme::Light))
+    .padding(sizes::SEP)
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
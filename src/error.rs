@@ -1,39 +1,511 @@
+use std::{error::Error as StdError, fmt::Display, io::ErrorKind};
+
 use kube::{
+    client::AuthError,
     config::{InferConfigError, KubeconfigError},
+    error::ErrorResponse,
     Error as KubeError,
 };
 
+use crate::custom_widgets::toast::{Status, Toast};
+
+/// The parts of a Kubernetes API `Status` response that are worth keeping around
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiStatus {
+    /// HTTP status code of the response
+    pub code: u16,
+    /// Machine readable reason, e.g. `Forbidden` or `AlreadyExists`
+    pub reason: String,
+    /// Human readable message from the API server
+    pub message: String,
+}
+
+impl From<ErrorResponse> for ApiStatus {
+    fn from(response: ErrorResponse) -> Self {
+        ApiStatus {
+            code: response.code,
+            reason: response.reason,
+            message: response.message,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct Error {
-    message: String,
+pub enum Error {
+    /// Nothing is listening at the cluster's address
+    ConnectionRefused {
+        message: String,
+    },
+    /// The TLS handshake with the cluster failed, usually because of a bad or unknown certificate
+    Tls {
+        message: String,
+    },
+    /// The credentials were rejected or couldn't be refreshed.
+    /// `status` is the API server's response, if it was the one that rejected them.
+    AuthExpired {
+        message: String,
+        status: Option<ApiStatus>,
+    },
+    /// The credentials in the kubeconfig couldn't be used to authenticate at all,
+    /// e.g. the exec plugin isn't installed or the token file is missing
+    Credentials {
+        message: String,
+    },
+    /// The user isn't allowed to `verb` the `resource`
+    Forbidden {
+        verb: Option<String>,
+        resource: Option<String>,
+        status: ApiStatus,
+    },
+    NotFound {
+        status: ApiStatus,
+    },
+    /// The resource was changed by someone else, or already exists
+    Conflict {
+        status: ApiStatus,
+    },
+    /// The cluster took too long to respond.
+    /// `status` is the API server's response, if the timeout was reported by it rather than the connection.
+    Timeout {
+        message: String,
+        status: Option<ApiStatus>,
+    },
+    /// Any other error response from the API server
+    Api {
+        status: ApiStatus,
+    },
+    /// The kubeconfig couldn't be read or is invalid
+    Config {
+        message: String,
+    },
+    Other {
+        message: String,
+    },
 }
 
 impl Error {
+    pub fn new(message: String) -> Self {
+        Error::Other { message }
+    }
+
+    /// Whether the credentials of the context need to be refreshed before anything else will work
+    pub fn is_auth_expired(&self) -> bool {
+        matches!(self, Error::AuthExpired { .. })
+    }
+
+    /// # Returns
+    /// The technical description of the error, for logging
     pub fn get_message(&self) -> String {
-        self.message.clone()
+        match self {
+            Error::ConnectionRefused { message }
+            | Error::Tls { message }
+            | Error::AuthExpired {
+                message,
+                status: None,
+            }
+            | Error::Credentials { message }
+            | Error::Timeout {
+                message,
+                status: None,
+            }
+            | Error::Config { message }
+            | Error::Other { message } => message.clone(),
+            Error::Forbidden { status, .. }
+            | Error::AuthExpired {
+                status: Some(status),
+                ..
+            }
+            | Error::NotFound { status }
+            | Error::Conflict { status }
+            | Error::Timeout {
+                status: Some(status),
+                ..
+            }
+            | Error::Api { status } => {
+                format!("{} ({}): {}", status.reason, status.code, status.message)
+            }
+        }
+    }
+
+    /// # Returns
+    /// An error toast that starts with `summary`, followed by how to fix the error,
+    /// with the technical description hidden in the details
+    pub fn to_toast(&self, summary: &str) -> Toast {
+        Toast::new(
+            Status::Danger,
+            "Error",
+            format!("{} {}", summary, self.get_remediation()),
+        )
+        .details(self.get_message())
+    }
+
+    /// # Returns
+    /// A sentence explaining what went wrong and what the user can do about it
+    pub fn get_remediation(&self) -> String {
+        match self {
+            Error::ConnectionRefused { .. } => String::from(
+                "The cluster refused the connection. Check that it's running and that the server address in your kubeconfig is correct.",
+            ),
+            Error::Tls { .. } => String::from(
+                "The cluster's certificate couldn't be verified. Check the certificate authority data in your kubeconfig.",
+            ),
+            Error::AuthExpired { .. } => String::from(
+                "Your credentials have expired or were rejected. Log in to the cluster again, then retry.",
+            ),
+            Error::Credentials { .. } => String::from(
+                "Your credentials couldn't be loaded. Check the user in your kubeconfig, e.g. that its exec plugin is installed and the files it points to exist.",
+            ),
+            Error::Forbidden {
+                verb: Some(verb),
+                resource: Some(resource),
+                ..
+            } => format!(
+                "You don't have permission to {} {}. Ask a cluster administrator for access, or switch to a different context.",
+                verb, resource
+            ),
+            Error::Forbidden { .. } => String::from(
+                "You don't have permission to do this. Ask a cluster administrator for access, or switch to a different context.",
+            ),
+            Error::NotFound { .. } => String::from(
+                "The resource doesn't exist anymore. It may have been deleted by someone else.",
+            ),
+            Error::Conflict { .. } => String::from(
+                "The resource was changed by someone else. Reload it and try again.",
+            ),
+            Error::Timeout { .. } => String::from(
+                "The cluster took too long to respond. Check your network connection and try again.",
+            ),
+            Error::Api { status } => format!("The cluster rejected the request: {}", status.message),
+            Error::Config { .. } => String::from(
+                "Your kubeconfig couldn't be read. Check that it exists and is valid YAML.",
+            ),
+            Error::Other { message } => message.clone(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_message())
     }
 }
 
 impl From<KubeError> for Error {
     fn from(kube_error: KubeError) -> Self {
-        Error {
-            message: kube_error.to_string(),
+        let message = describe_chain(&kube_error);
+        match kube_error {
+            KubeError::Api(response) => from_status(response.into()),
+            KubeError::OpensslTls(..) => Error::Tls { message },
+            KubeError::Auth(auth_error) => from_auth_error(auth_error, message),
+            KubeError::InferConfig(..) => Error::Config { message },
+            // connection problems are wrapped several layers deep by hyper and tower
+            KubeError::HyperError(..) | KubeError::Service(..) => {
+                from_source_chain(&kube_error, message)
+            }
+            _ => Error::Other { message },
+        }
+    }
+}
+
+fn from_status(status: ApiStatus) -> Error {
+    match status.code {
+        401 => Error::AuthExpired {
+            message: status.message.clone(),
+            status: Some(status),
+        },
+        403 => {
+            let (verb, resource) = parse_forbidden_message(&status.message);
+            Error::Forbidden {
+                verb,
+                resource,
+                status,
+            }
         }
+        404 => Error::NotFound { status },
+        409 => Error::Conflict { status },
+        408 | 504 => Error::Timeout {
+            message: status.message.clone(),
+            status: Some(status),
+        },
+        _ => Error::Api { status },
     }
 }
 
+/// Tells credentials that were rejected, which logging in again fixes,
+/// apart from credentials that couldn't be used at all, e.g. because the exec plugin isn't installed
+fn from_auth_error(auth_error: AuthError, message: String) -> Error {
+    match auth_error {
+        // the plugin ran but refused to hand out a token, e.g. because the user's session expired
+        AuthError::AuthExecRun { .. } | AuthError::UnrefreshableTokenResponse => {
+            Error::AuthExpired {
+                message,
+                status: None,
+            }
+        }
+        _ => Error::Credentials { message },
+    }
+}
+
+/// # Returns
+/// The description of the error, followed by the descriptions of each of the errors that caused it
+fn describe_chain(error: &(dyn StdError + 'static)) -> String {
+    let mut description = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        description.push_str(&format!("\ncaused by: {}", error));
+        source = error.source();
+    }
+    description
+}
+
+/// Finds the underlying cause of a connection error by walking through the errors that caused it
+fn from_source_chain(error: &(dyn StdError + 'static), message: String) -> Error {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(io_error) = error.downcast_ref::<std::io::Error>() {
+            match io_error.kind() {
+                ErrorKind::ConnectionRefused => return Error::ConnectionRefused { message },
+                ErrorKind::TimedOut => {
+                    return Error::Timeout {
+                        message,
+                        status: None,
+                    }
+                }
+                _ => {}
+            }
+        }
+        let description = error.to_string().to_lowercase();
+        if description.contains("certificate") || description.contains("ssl") {
+            return Error::Tls { message };
+        }
+        if description.contains("timed out") || description.contains("deadline has elapsed") {
+            return Error::Timeout {
+                message,
+                status: None,
+            };
+        }
+        source = error.source();
+    }
+    Error::Other { message }
+}
+
+/// Pulls the verb and resource out of a message like
+/// `pods is forbidden: User "jane" cannot list resource "pods" in API group "" in the namespace "default"`
+fn parse_forbidden_message(message: &str) -> (Option<String>, Option<String>) {
+    let verb = message
+        .split_once(" cannot ")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .map(String::from);
+    let resource = message
+        .split_once("resource \"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(resource, _)| String::from(resource));
+    (verb, resource)
+}
+
 impl From<InferConfigError> for Error {
     fn from(kube_error: InferConfigError) -> Self {
-        Error {
+        Error::Config {
             message: kube_error.to_string(),
         }
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(io_error: std::io::Error) -> Self {
+        Error::Other {
+            message: io_error.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(json_error: serde_json::Error) -> Self {
+        Error::Other {
+            message: json_error.to_string(),
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(yaml_error: serde_yaml::Error) -> Self {
+        Error::Other {
+            message: format!("The YAML is invalid: {}", yaml_error),
+        }
+    }
+}
+
 impl From<KubeconfigError> for Error {
     fn from(kube_error: KubeconfigError) -> Self {
-        Error {
+        Error::Config {
             message: kube_error.to_string(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::*;
+
+    /// An error with a cause, standing in for the layers of errors hyper and tower wrap connection errors in
+    #[derive(Debug)]
+    struct Wrapped {
+        description: &'static str,
+        source: Box<dyn StdError + 'static>,
+    }
+
+    impl fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.description)
+        }
+    }
+
+    impl StdError for Wrapped {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            Some(self.source.as_ref())
+        }
+    }
+
+    fn wrapped(description: &'static str, source: impl StdError + 'static) -> Wrapped {
+        Wrapped {
+            description,
+            source: Box::new(source),
+        }
+    }
+
+    fn status(code: u16, reason: &str, message: &str) -> ApiStatus {
+        ApiStatus {
+            code,
+            reason: String::from(reason),
+            message: String::from(message),
+        }
+    }
+
+    #[test]
+    fn finds_refused_connections_in_the_source_chain() {
+        let error = wrapped(
+            "client error (Connect)",
+            wrapped(
+                "tcp connect error: Connection refused (os error 111)",
+                std::io::Error::from(ErrorKind::ConnectionRefused),
+            ),
+        );
+
+        assert!(matches!(
+            from_source_chain(&error, String::new()),
+            Error::ConnectionRefused { .. }
+        ));
+    }
+
+    #[test]
+    fn finds_certificate_errors_in_the_source_chain() {
+        let error = wrapped(
+            "client error (Connect)",
+            std::io::Error::other("error:0A000086:SSL routines:tls_post_process_server_certificate:certificate verify failed:../ssl/statem/statem_clnt.c:1889:",
+            ),
+        );
+
+        assert!(matches!(
+            from_source_chain(&error, String::new()),
+            Error::Tls { .. }
+        ));
+    }
+
+    #[test]
+    fn finds_timeouts_in_the_source_chain() {
+        let error = wrapped(
+            "ServiceError: deadline has elapsed",
+            std::io::Error::other("deadline has elapsed"),
+        );
+
+        assert!(matches!(
+            from_source_chain(&error, String::new()),
+            Error::Timeout { status: None, .. }
+        ));
+    }
+
+    #[test]
+    fn falls_back_to_other_for_unknown_connection_errors() {
+        let error = wrapped(
+            "client error (Connect)",
+            std::io::Error::other("dns error: failed to lookup address information"),
+        );
+
+        assert!(matches!(
+            from_source_chain(&error, String::new()),
+            Error::Other { .. }
+        ));
+    }
+
+    #[test]
+    fn parses_namespaced_forbidden_messages() {
+        let message = r#"pods is forbidden: User "system:serviceaccount:default:viewer" cannot delete resource "pods" in API group "" in the namespace "default""#;
+
+        assert_eq!(
+            parse_forbidden_message(message),
+            (Some(String::from("delete")), Some(String::from("pods")))
+        );
+    }
+
+    #[test]
+    fn parses_cluster_scoped_forbidden_messages() {
+        let message = r#"namespaces is forbidden: User "jane" cannot list resource "namespaces" in API group "" at the cluster scope"#;
+
+        assert_eq!(
+            parse_forbidden_message(message),
+            (Some(String::from("list")), Some(String::from("namespaces")))
+        );
+    }
+
+    #[test]
+    fn parses_forbidden_messages_about_subresources() {
+        let message = r#"pods "nginx-7c5ddbdf54-x8kqz" is forbidden: User "jane" cannot get resource "pods/log" in API group "" in the namespace "default""#;
+
+        assert_eq!(
+            parse_forbidden_message(message),
+            (Some(String::from("get")), Some(String::from("pods/log")))
+        );
+    }
+
+    #[test]
+    fn leaves_out_what_isnt_in_forbidden_messages() {
+        let message =
+            "unable to create new content in namespace staging because it is being terminated";
+
+        assert_eq!(parse_forbidden_message(message), (None, None));
+    }
+
+    #[test]
+    fn keeps_the_status_of_rejected_credentials_and_timeouts() {
+        let unauthorized = status(401, "Unauthorized", "Unauthorized");
+        let timeout = status(
+            504,
+            "Timeout",
+            "Timeout: request did not complete within the allotted timeout",
+        );
+
+        assert!(matches!(
+            from_status(unauthorized.clone()),
+            Error::AuthExpired { status: Some(status), .. } if status == unauthorized
+        ));
+        assert!(matches!(
+            from_status(timeout.clone()),
+            Error::Timeout { status: Some(status), .. } if status == timeout
+        ));
+    }
+
+    #[test]
+    fn only_treats_rejected_credentials_as_expired() {
+        assert!(
+            from_auth_error(AuthError::UnrefreshableTokenResponse, String::new()).is_auth_expired()
+        );
+        // e.g. the exec plugin isn't installed, which logging in again won't fix
+        let missing_plugin = AuthError::AuthExecStart(std::io::Error::from(ErrorKind::NotFound));
+        assert!(matches!(
+            from_auth_error(missing_plugin, String::new()),
+            Error::Credentials { .. }
+        ));
+    }
+}
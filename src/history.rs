@@ -0,0 +1,127 @@
+use chrono::Local;
+use iced::{
+    widget::{button, column, container, horizontal_space, row, scrollable, text},
+    Alignment, Element, Length, Padding,
+};
+
+use crate::{
+    audit::{self, AuditEntry, Outcome},
+    button_theme::{as_button_theme, ButtonTheme},
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    messages::Message,
+    sizes,
+};
+
+/// Lists the changes that were made to clusters from the app, newest first
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: Vec<AuditEntry>,
+}
+
+impl History {
+    pub fn new(entries: Vec<AuditEntry>) -> History {
+        History { entries }
+    }
+
+    /// Adds an entry that was recorded while the history is open
+    pub fn push(&mut self, entry: AuditEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let entries: Vec<Element<Message>> = self
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| entry_view(entry, palette))
+            .collect();
+        let list: Element<Message> = if entries.is_empty() {
+            text("Nothing has been changed from iced-k8s yet")
+                .style(palette.secondary_text)
+                .into()
+        } else {
+            scrollable(column(entries).spacing(sizes::SEP))
+                .height(Length::Fill)
+                .into()
+        };
+        let file = audit::audit_file()
+            .map(|path| format!("Recorded in {}", path.display()))
+            .unwrap_or_default();
+
+        container(
+            column![
+                row![
+                    text("History").size(sizes::H2),
+                    horizontal_space(Length::Fill),
+                    button(container(text("Close")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .on_press(Message::HistoryClosed)
+                    .style(as_button_theme(ButtonTheme::Primary)),
+                ]
+                .spacing(sizes::SEP)
+                .align_items(Alignment::Center),
+                text(file)
+                    .size(sizes::P * 3.0 / 4.0)
+                    .style(palette.secondary_text),
+                list,
+            ]
+            .max_width(800)
+            .spacing(sizes::SEP),
+        )
+        .style(as_container_theme(ContainerTheme::Light))
+        .padding(sizes::SEP)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .into()
+    }
+}
+
+fn entry_view(entry: &AuditEntry, palette: Palette) -> Element<'_, Message> {
+    let (outcome, colour) = match &entry.outcome {
+        Outcome::Succeeded => (String::from("Succeeded"), palette.success),
+        Outcome::Failed { error } => (format!("Failed: {}", error), palette.danger),
+    };
+    let mut user = entry
+        .user
+        .clone()
+        .unwrap_or_else(|| String::from("Unknown user"));
+    if let Some(impersonating) = &entry.impersonating {
+        user.push_str(&format!(" as {}", impersonating));
+    }
+
+    column![
+        row![
+            text(format!(
+                "{} {} {}",
+                entry.action, entry.resource_type, entry.name
+            ))
+            .style(palette.text),
+            horizontal_space(Length::Fill),
+            text(
+                entry
+                    .timestamp
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            )
+            .size(sizes::P * 3.0 / 4.0)
+            .style(palette.secondary_text),
+        ]
+        .align_items(Alignment::Center),
+        text(format!(
+            "{} / {} by {}",
+            entry.context, entry.namespace, user
+        ))
+        .size(sizes::P)
+        .style(palette.secondary_text),
+        text(outcome).size(sizes::P).style(colour),
+    ]
+    .spacing(sizes::SMOL_SEP)
+    .into()
+}
@@ -1,61 +1,335 @@
-use std::fmt::Display;
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    sync::{Arc, RwLock, RwLockReadGuard},
+    time::{Duration, Instant},
+};
 
 use iced::{
     widget::{horizontal_space, row, text},
     Element,
 };
-use kube::Config;
+use kube::{Client, Config};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    colours::Palette, constants, error::Error, messages::Message, permissions::Rules, sizes,
+};
+
+/// A user, and optionally groups, that requests are sent on behalf of, like `kubectl --as` does
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Impersonation {
+    pub user: String,
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
+
+impl Display for Impersonation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.user)?;
+        if !self.groups.is_empty() {
+            write!(f, " (groups: {})", self.groups.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Which actions that change the cluster are allowed in a context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Safety {
+    Normal,
+    /// Changes have to be confirmed by typing the name of the resource
+    Protected,
+    /// Nothing can be changed
+    ReadOnly,
+}
+
+/// The parts of a context that are shared between every copy of it
+struct Shared {
+    /// The config as it was read from the kubeconfig, without the impersonation applied
+    config: Config,
+    impersonation: Option<Impersonation>,
+    /// Set by the user for the context
+    read_only: bool,
+    /// Set when the name of the context matches one of the protected patterns in the settings
+    protected: bool,
+    /// Created when it's first needed, so that every request of the context reuses its connections
+    client: Option<Client>,
+    /// What the user may do in each namespace, along with when it was fetched
+    rules: HashMap<String, (Instant, Rules)>,
+}
+
+impl Debug for Shared {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shared")
+            .field("config", &self.config)
+            .field("impersonation", &self.impersonation)
+            .field("read_only", &self.read_only)
+            .field("protected", &self.protected)
+            .field("has_client", &self.client.is_some())
+            .field("rules", &self.rules)
+            .finish()
+    }
+}
 
-use crate::{colours, messages::Message, sizes};
+impl Shared {
+    /// The impersonation is applied on top of the kubeconfig, so that stopping it goes back to
+    /// whatever the kubeconfig itself says
+    fn effective_config(&self) -> Config {
+        let mut config = self.config.clone();
+        if let Some(impersonation) = &self.impersonation {
+            config.auth_info.impersonate = Some(impersonation.user.clone());
+            config.auth_info.impersonate_groups = if impersonation.groups.is_empty() {
+                None
+            } else {
+                Some(impersonation.groups.clone())
+            };
+        }
+        config
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct KubeContext {
-    config: Config,
+    name: String,
+    /// Shared between every copy of the context, so that every tab uses the same client,
+    /// and re-authenticating updates every tab at once
+    shared: Arc<RwLock<Shared>>,
     namespace: String,
+    /// Whether the workloads in every namespace are listed, instead of only the ones in `namespace`
+    all_namespaces: bool,
+    /// Name of the user of the context in the kubeconfig, if it was read from one
+    user: Option<String>,
 }
 
 impl KubeContext {
-    pub fn new(config: Config, namespace: String) -> KubeContext {
-        KubeContext { config, namespace }
+    pub fn new(
+        name: String,
+        config: Config,
+        namespace: String,
+        user: Option<String>,
+    ) -> KubeContext {
+        KubeContext {
+            name,
+            shared: Arc::new(RwLock::new(Shared {
+                config,
+                impersonation: None,
+                read_only: false,
+                protected: false,
+                client: None,
+                rules: HashMap::new(),
+            })),
+            namespace,
+            all_namespaces: false,
+            user,
+        }
+    }
+
+    /// Returns a copy of this context that points to a different namespace
+    pub fn with_namespace(&self, namespace: String) -> KubeContext {
+        KubeContext {
+            namespace,
+            all_namespaces: false,
+            ..self.clone()
+        }
     }
 
-    pub fn view(&self) -> Element<Message> {
-        row![
-            text("Cluster:").style(colours::get_grey()),
-            text(self.config.cluster_url.to_string()).style(colours::get_white()),
+    /// Returns a copy of this context that points to every namespace
+    pub fn with_all_namespaces(&self) -> KubeContext {
+        KubeContext {
+            all_namespaces: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let mut view = row![
+            text("Cluster:").style(palette.secondary_text),
+            text(self.get_config().cluster_url.to_string()).style(palette.on_accent),
             horizontal_space(sizes::SEP),
-            text("Namespace:").style(colours::get_grey()),
-            text(self.namespace.clone()).style(colours::get_white()),
+            text("Namespace:").style(palette.secondary_text),
+            text(self.get_namespace_label()).style(palette.on_accent),
         ]
-        .spacing(sizes::SEP)
-        .into()
+        .spacing(sizes::SEP);
+        if self.get_safety() == Safety::ReadOnly {
+            view = view
+                .push(horizontal_space(sizes::SEP))
+                .push(text("Read-only").style(palette.secondary_text));
+        }
+        // what's shown might not be what the user can see themselves, so make it hard to miss
+        if let Some(impersonation) = self.get_impersonation() {
+            view = view
+                .push(horizontal_space(sizes::SEP))
+                .push(text(format!("Impersonating: {}", impersonation)).style(palette.danger));
+        }
+        view.into()
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// # Returns
+    /// The name of the user in the kubeconfig, or the username from the credentials if there isn't one
+    pub fn get_user(&self) -> Option<String> {
+        self.user
+            .clone()
+            .or_else(|| self.read_shared().config.auth_info.username.clone())
     }
 
-    pub fn get_config(&self) -> &Config {
-        &self.config
+    /// # Returns
+    /// The config that requests are sent with, including the impersonation
+    pub fn get_config(&self) -> Config {
+        self.read_shared().effective_config()
+    }
+
+    /// Replaces the config of this context and every copy of it, e.g. after the user logged in again.
+    /// The client is recreated the next time it's needed, so that it uses the new credentials.
+    pub fn set_config(&self, config: Config) {
+        let mut shared = self
+            .shared
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        shared.config = config;
+        shared.client = None;
+        // different credentials can have different permissions
+        shared.rules.clear();
+    }
+
+    pub fn get_impersonation(&self) -> Option<Impersonation> {
+        self.read_shared().impersonation.clone()
+    }
+
+    /// Sends the requests of this context and every copy of it on behalf of another user,
+    /// or as the user from the kubeconfig again if `impersonation` is `None`
+    pub fn set_impersonation(&self, impersonation: Option<Impersonation>) {
+        let mut shared = self
+            .shared
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        shared.impersonation = impersonation;
+        shared.client = None;
+        shared.rules.clear();
+    }
+
+    /// # Returns
+    /// Which actions that change the cluster are allowed. Read-only wins over protected.
+    pub fn get_safety(&self) -> Safety {
+        let shared = self.read_shared();
+        if shared.read_only {
+            Safety::ReadOnly
+        } else if shared.protected {
+            Safety::Protected
+        } else {
+            Safety::Normal
+        }
+    }
+
+    /// Whether a change was confirmed with what the user `typed`.
+    /// In a protected context the name of the context has to be typed,
+    /// whether one resource or several are being changed.
+    pub fn is_confirmed(&self, typed: &str) -> bool {
+        self.get_safety() != Safety::Protected || typed == self.get_name()
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_shared().read_only
+    }
+
+    /// Sets whether the context is read-only, for this context and every copy of it
+    pub fn set_read_only(&self, read_only: bool) {
+        self.shared
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .read_only = read_only;
+    }
+
+    /// Sets whether the context is protected, for this context and every copy of it
+    pub fn set_protected(&self, protected: bool) {
+        self.shared
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .protected = protected;
+    }
+
+    /// # Returns
+    /// The client that every copy of this context shares, creating it if this is the first request.
+    /// This has to be called from within the async runtime, since the client spawns a background task.
+    pub fn get_client(&self) -> Result<Client, Error> {
+        if let Some(client) = self.read_shared().client.as_ref() {
+            return Ok(client.clone());
+        }
+        let mut shared = self
+            .shared
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // another request may have created it while the lock was released
+        if let Some(client) = shared.client.as_ref() {
+            return Ok(client.clone());
+        }
+        let client = Client::try_from(shared.effective_config())?;
+        shared.client = Some(client.clone());
+        Ok(client)
+    }
+
+    /// # Returns
+    /// What the user may do in the namespace, if it was fetched recently
+    pub fn get_cached_rules(&self, namespace: &str) -> Option<Rules> {
+        self.read_shared()
+            .rules
+            .get(namespace)
+            .filter(|(fetched, _)| {
+                fetched.elapsed() < Duration::from_secs(constants::PERMISSION_CACHE)
+            })
+            .map(|(_, rules)| rules.clone())
+    }
+
+    pub fn cache_rules(&self, namespace: String, rules: Rules) {
+        self.shared
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .rules
+            .insert(namespace, (Instant::now(), rules));
+    }
+
+    fn read_shared(&self) -> RwLockReadGuard<'_, Shared> {
+        self.shared
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 
     pub fn get_namespace(&self) -> String {
         self.namespace.clone()
     }
+
+    pub fn is_all_namespaces(&self) -> bool {
+        self.all_namespaces
+    }
+
+    /// # Returns
+    /// The namespace, or a description of it if every namespace is being listed
+    pub fn get_namespace_label(&self) -> String {
+        if self.all_namespaces {
+            String::from("All namespaces")
+        } else {
+            self.namespace.clone()
+        }
+    }
 }
 
 impl Display for KubeContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.config.auth_info.username.as_ref() {
+        let config = self.get_config();
+        match config.auth_info.username.as_ref() {
             Some(username) => {
                 write!(
                     f,
                     "{}/{}/{}",
-                    self.config.cluster_url, self.config.default_namespace, username
+                    config.cluster_url, config.default_namespace, username
                 )
             }
             None => {
-                write!(
-                    f,
-                    "{}/{}",
-                    self.config.cluster_url, self.config.default_namespace
-                )
+                write!(f, "{}/{}", config.cluster_url, config.default_namespace)
             }
         }
     }
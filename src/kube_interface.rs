@@ -1,216 +1,804 @@
+use std::{fmt::Debug, path::PathBuf, time::Duration};
+
+use iced::futures::try_join;
+
 use k8s_openapi::api::{
     apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
-    core::v1::Pod,
+    authorization::v1::{
+        ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+        SelfSubjectRulesReview, SelfSubjectRulesReviewSpec,
+    },
+    core::v1::{Namespace, Pod},
 };
+use k8s_openapi::{apimachinery::pkg::apis::meta::v1::ObjectMeta, NamespaceResourceScope};
 use kube::{
-    api::{ListParams, DeleteParams},
+    api::{DeleteParams, DynamicObject, ListParams, LogParams, Patch, PatchParams, PostParams},
     config::{KubeConfigOptions, Kubeconfig},
-    Api, Client, Config,
+    Api, Client, Config, Resource,
 };
+use serde::de::DeserializeOwned;
 
 use crate::{
-    cluster_object::ClusterObject, error::Error, kube_context::KubeContext,
-    resource_type::ResourceType, workloads::Workloads,
+    cluster_object::ClusterObject,
+    error::{ApiStatus, Error},
+    kube_context::KubeContext,
+    kubeconfig::{self, MergedKubeconfig},
+    metadata::{Metadata, MetadataPatch},
+    permissions::{Access, AccessQuery, Rules},
+    resource_type::ResourceType,
+    settings::DeletePropagation,
+    workloads::Workloads,
 };
 
-pub async fn fetch_current_context() -> Result<KubeContext, Error> {
-    let config = Config::infer().await?;
-    let namespace = config.default_namespace.clone();
-    Ok(KubeContext::new(config, namespace))
+/// Loads the current context of the merged kubeconfig files,
+/// or the in-cluster config if there isn't a kubeconfig
+#[tracing::instrument(err)]
+pub async fn fetch_current_context(kubeconfig_files: Vec<PathBuf>) -> Result<KubeContext, Error> {
+    let current_context = kubeconfig::read_merged(&kubeconfig_files)
+        .ok()
+        .and_then(|merged| merged.kubeconfig.current_context);
+    match current_context {
+        Some(name) => load_named_context(name, kubeconfig_files).await,
+        None => {
+            // there is no context name when the config is inferred from the in-cluster environment
+            let config = Config::infer().await?;
+            let namespace = config.default_namespace.clone();
+            let name = config.cluster_url.to_string();
+            Ok(KubeContext::new(name, config, namespace, None))
+        }
+    }
 }
 
-pub async fn fetch_cluster_state(context: KubeContext) -> Result<Workloads, Error> {
-    let client = Client::try_from(context.get_config().to_owned())?;
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %context.get_namespace_label(),
+    ),
+    err
+)]
+pub async fn fetch_cluster_state(
+    context: KubeContext,
+    resource_type: Option<ResourceType>,
+) -> Result<Workloads, Error> {
+    let client = context.get_client()?;
+    // lists every resource type unless only one was asked for
+    let wanted = |r#type: ResourceType| resource_type.is_none_or(|wanted| wanted == r#type);
+    // types the user may not list are skipped instead of being sent to fail.
+    // rules can only be reviewed for a single namespace, so every type is tried for all namespaces.
+    let rules = if context.is_all_namespaces() {
+        None
+    } else {
+        fetch_rules(context.clone())
+            .await
+            .inspect_err(|error| {
+                tracing::debug!(
+                    "Listing every type, since the rules couldn't be reviewed: {}",
+                    error
+                )
+            })
+            .ok()
+    };
+    let rules = rules.as_ref();
 
-    let mut cluster_objects: Vec<ClusterObject> = vec![];
+    // the lists are independent of each other, so they're sent at the same time
+    let (deployments, daemonsets, replicasets, statefulsets, pods) = try_join!(
+        list_objects::<Deployment>(
+            wanted(ResourceType::Deployment),
+            rules,
+            &client,
+            &context,
+            ResourceType::Deployment,
+            |deployment| {
+                let available = deployment.status.as_ref()?.available_replicas.unwrap_or(0);
+                let requested = deployment.spec.as_ref()?.replicas.unwrap_or(0);
+                Some(format!("{}/{}", available, requested))
+            },
+        ),
+        list_objects::<DaemonSet>(
+            wanted(ResourceType::DaemonSet),
+            rules,
+            &client,
+            &context,
+            ResourceType::DaemonSet,
+            |_daemonset| None,
+        ),
+        list_objects::<ReplicaSet>(
+            wanted(ResourceType::ReplicaSet),
+            rules,
+            &client,
+            &context,
+            ResourceType::ReplicaSet,
+            |replicaset| {
+                let available = replicaset.status.as_ref()?.available_replicas.unwrap_or(0);
+                let requested = replicaset.spec.as_ref()?.replicas.unwrap_or(0);
+                Some(format!("{}/{}", available, requested))
+            },
+        ),
+        list_objects::<StatefulSet>(
+            wanted(ResourceType::StatefulSet),
+            rules,
+            &client,
+            &context,
+            ResourceType::StatefulSet,
+            |_statefulset| None,
+        ),
+        list_objects::<Pod>(
+            wanted(ResourceType::Pod),
+            rules,
+            &client,
+            &context,
+            ResourceType::Pod,
+            |pod| pod.status.as_ref()?.phase.clone(),
+        ),
+    )?;
 
-    let deployments: Api<Deployment> = Api::namespaced(client.clone(), &context.get_namespace());
-    cluster_objects.extend(
-        deployments
-            .list(&ListParams::default())
-            .await?
-            .items
-            .iter()
-            .map(|deployment| {
-                let details = if deployment.status != None && deployment.spec != None {
-                    let available = deployment
-                        .status
-                        .clone()
-                        .unwrap()
-                        .available_replicas
-                        .unwrap_or(0);
-                    let requested = deployment.spec.clone().unwrap().replicas.unwrap_or(0);
-                    Some(format!("{}/{}", available, requested))
-                } else {
-                    None
-                };
-                ClusterObject::new(
-                    deployment.metadata.name.as_ref().unwrap().to_owned(),
-                    ResourceType::Deployment,
-                    details,
-                )
-            }),
-    );
-    let daemonsets: Api<DaemonSet> = Api::namespaced(client.clone(), &context.get_namespace());
-    cluster_objects.extend(
-        daemonsets
-            .list(&ListParams::default())
-            .await?
-            .items
-            .iter()
-            .map(|daemonset| {
-                ClusterObject::new(
-                    daemonset.metadata.name.as_ref().unwrap().to_owned(),
-                    ResourceType::DaemonSet,
-                    None,
-                )
-            }),
-    );
-    let replicasets: Api<ReplicaSet> = Api::namespaced(client.clone(), &context.get_namespace());
-    cluster_objects.extend(
-        replicasets
-            .list(&ListParams::default())
-            .await?
-            .items
-            .iter()
-            .map(|replicaset| {
-                let details = if replicaset.status != None && replicaset.spec != None {
-                    let available = replicaset
-                        .status
-                        .clone()
-                        .unwrap()
-                        .available_replicas
-                        .unwrap_or(0);
-                    let requested = replicaset.spec.clone().unwrap().replicas.unwrap_or(0);
-                    Some(format!("{}/{}", available, requested))
-                } else {
-                    None
-                };
-                ClusterObject::new(
-                    replicaset.metadata.name.as_ref().unwrap().to_owned(),
-                    ResourceType::ReplicaSet,
-                    details,
-                )
-            }),
-    );
-    let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), &context.get_namespace());
-    cluster_objects.extend(
-        statefulsets
-            .list(&ListParams::default())
-            .await?
-            .items
-            .iter()
-            .map(|statefulset| {
-                ClusterObject::new(
-                    statefulset.metadata.name.as_ref().unwrap().to_owned(),
-                    ResourceType::StatefulSet,
-                    None,
-                )
-            }),
+    let mut cluster_objects: Vec<ClusterObject> = vec![];
+    let mut forbidden: Vec<ResourceType> = vec![];
+    for (r#type, listed) in [
+        (ResourceType::Deployment, deployments),
+        (ResourceType::DaemonSet, daemonsets),
+        (ResourceType::ReplicaSet, replicasets),
+        (ResourceType::StatefulSet, statefulsets),
+        (ResourceType::Pod, pods),
+    ] {
+        match listed {
+            Some(objects) => cluster_objects.extend(objects),
+            None => forbidden.push(r#type),
+        }
+    }
+    tracing::debug!(
+        count = cluster_objects.len(),
+        forbidden = forbidden.len(),
+        "loaded workloads"
     );
-    let pods: Api<Pod> = Api::namespaced(client.clone(), &context.get_namespace());
-    cluster_objects.extend(
-        pods.list(&ListParams::default())
-            .await?
-            .items
+    Ok(Workloads::new(cluster_objects, forbidden, rules.cloned()))
+}
+
+/// Lists the resources of type `K`, if they're `wanted`
+///
+/// # Returns
+/// A cluster object for each of the resources, with the details given by `details`,
+/// or `None` if the user isn't permitted to list them
+async fn list_objects<K>(
+    wanted: bool,
+    rules: Option<&Rules>,
+    client: &Client,
+    context: &KubeContext,
+    r#type: ResourceType,
+    details: fn(&K) -> Option<String>,
+) -> Result<Option<Vec<ClusterObject>>, Error>
+where
+    K: Resource<Scope = NamespaceResourceScope> + Clone + DeserializeOwned + Debug,
+    <K as Resource>::DynamicType: Default,
+{
+    if !wanted {
+        return Ok(Some(vec![]));
+    }
+    let api_resource = r#type.api_resource();
+    let allowed = rules
+        .and_then(|rules| rules.allows("list", &api_resource.group, &api_resource.plural, None));
+    if allowed == Some(false) {
+        return Ok(None);
+    }
+    let api: Api<K> = list_api(client.clone(), context);
+    let list = match api.list(&ListParams::default()).await.map_err(Error::from) {
+        Ok(list) => list,
+        Err(Error::Forbidden { .. }) => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    Ok(Some(
+        list.items
             .iter()
-            .map(|pod| {
-                let details = match &pod.status {
-                    Some(status) => status.phase.clone(),
-                    None => None,
-                };
+            .map(|resource| {
                 ClusterObject::new(
-                    pod.metadata.name.as_ref().unwrap().to_owned(),
-                    ResourceType::Pod,
-                    details,
+                    resource.meta().name.clone().unwrap_or_default(),
+                    namespace_of(resource.meta(), context),
+                    r#type,
+                    details(resource),
                 )
+            })
+            .collect(),
+    ))
+}
+
+/// Asks the API server whether the user may do what `query` describes, in the namespace of `context`
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %context.get_namespace_label(),
+        query = %query,
+    ),
+    err
+)]
+pub async fn can_i(context: KubeContext, query: AccessQuery) -> Result<Access, Error> {
+    let reviews: Api<SelfSubjectAccessReview> = Api::all(context.get_client()?);
+    let review = SelfSubjectAccessReview {
+        spec: SelfSubjectAccessReviewSpec {
+            resource_attributes: Some(ResourceAttributes {
+                // asking about every namespace means asking about the whole cluster
+                namespace: (!context.is_all_namespaces()).then(|| context.get_namespace()),
+                verb: Some(query.verb),
+                group: Some(query.group),
+                resource: Some(query.resource),
+                subresource: query.subresource,
+                name: query.name,
+                version: None,
             }),
-    );
+            non_resource_attributes: None,
+        },
+        ..Default::default()
+    };
+    let status = reviews
+        .create(&PostParams::default(), &review)
+        .await?
+        .status
+        .ok_or_else(|| Error::new(String::from("The API server didn't review the access")))?;
+    Ok(status.into())
+}
 
-    Ok(Workloads::new(cluster_objects))
+/// Reviews what the user may do in the namespace of `context`, reusing a recent review if there is one
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %context.get_namespace(),
+    ),
+    err
+)]
+pub async fn fetch_rules(context: KubeContext) -> Result<Rules, Error> {
+    let namespace = context.get_namespace();
+    if let Some(rules) = context.get_cached_rules(&namespace) {
+        return Ok(rules);
+    }
+    let reviews: Api<SelfSubjectRulesReview> = Api::all(context.get_client()?);
+    let review = SelfSubjectRulesReview {
+        spec: SelfSubjectRulesReviewSpec {
+            namespace: Some(namespace.clone()),
+        },
+        ..Default::default()
+    };
+    let status = reviews
+        .create(&PostParams::default(), &review)
+        .await?
+        .status
+        .ok_or_else(|| Error::new(String::from("The API server didn't review the rules")))?;
+    let rules = Rules::new(status);
+    context.cache_rules(namespace, rules.clone());
+    Ok(rules)
 }
 
-pub async fn get_all_contexts() -> Result<Vec<String>, Error> {
-    let kube_config = Kubeconfig::read()?;
-    Ok(kube_config
-        .contexts
-        .iter()
-        .map(|kube_ctx| kube_ctx.name.clone())
-        .collect())
+/// # Returns
+/// An API for listing resources in the namespace given by `context`, or in every namespace
+fn list_api<K>(client: Client, context: &KubeContext) -> Api<K>
+where
+    K: Resource<Scope = NamespaceResourceScope>,
+    <K as Resource>::DynamicType: Default,
+{
+    if context.is_all_namespaces() {
+        Api::all(client)
+    } else {
+        Api::namespaced(client, &context.get_namespace())
+    }
 }
 
-pub async fn load_named_context(name: String) -> Result<KubeContext, Error> {
-    let config = Config::from_kubeconfig(&KubeConfigOptions {
-        context: Some(name),
-        cluster: None,
-        user: None,
-    })
-    .await?;
+fn namespace_of(metadata: &ObjectMeta, context: &KubeContext) -> String {
+    metadata
+        .namespace
+        .clone()
+        .unwrap_or_else(|| context.get_namespace())
+}
+
+/// # Returns
+/// The merged kubeconfig files, along with the file each context came from
+#[tracing::instrument(err)]
+pub async fn get_all_contexts(kubeconfig_files: Vec<PathBuf>) -> Result<MergedKubeconfig, Error> {
+    kubeconfig::read_merged(&kubeconfig_files)
+}
+
+/// Reads the config of the context named `name` from the merged kubeconfig files
+async fn load_config(name: &str, kubeconfig_files: &[PathBuf]) -> Result<Config, Error> {
+    let merged = kubeconfig::read_merged(kubeconfig_files)?;
+    Ok(Config::from_custom_kubeconfig(
+        merged.kubeconfig,
+        &KubeConfigOptions {
+            context: Some(name.to_owned()),
+            cluster: None,
+            user: None,
+        },
+    )
+    .await?)
+}
+
+#[tracing::instrument(err)]
+pub async fn load_named_context(
+    name: String,
+    kubeconfig_files: Vec<PathBuf>,
+) -> Result<KubeContext, Error> {
+    let config = load_config(&name, &kubeconfig_files).await?;
     let namespace = config.default_namespace.clone();
-    let kube_ctx = KubeContext::new(config, namespace);
+    let user = kubeconfig::read_merged(&kubeconfig_files)
+        .ok()
+        .and_then(|merged| merged.contexts.into_iter().find(|entry| entry.name == name))
+        .and_then(|entry| entry.user);
+    let kube_ctx = KubeContext::new(name, config, namespace, user);
     check_cluster_accessible(kube_ctx.clone()).await?;
     Ok(kube_ctx)
 }
 
-/// Deletes the given `cluster_object` from the cluster and namespace given by `context`
+/// How long to wait for a cluster to respond when checking whether each context is reachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Checks whether the cluster of the context named `name` can be reached, without connecting to it.
+/// `kubeconfig` is the already merged kubeconfig, so that it isn't read again for every context.
 ///
 /// # Returns
-/// The cluster object that was deleted, or an error if the cluster object couldn't be deleted.
-pub async fn delete(context: KubeContext, cluster_object: ClusterObject) -> Result<ClusterObject, Error> {
-    let client = Client::try_from(context.get_config().to_owned())?;
+/// The version of Kubernetes the cluster is running, or an error if it couldn't be reached
+#[tracing::instrument(skip(kubeconfig), err)]
+pub async fn probe_context(name: String, kubeconfig: Kubeconfig) -> Result<String, Error> {
+    let mut config = Config::from_custom_kubeconfig(
+        kubeconfig,
+        &KubeConfigOptions {
+            context: Some(name),
+            cluster: None,
+            user: None,
+        },
+    )
+    .await?;
+    // unreachable clusters would otherwise keep the probe going for the default 30 seconds
+    config.connect_timeout = Some(PROBE_TIMEOUT);
+    config.read_timeout = Some(PROBE_TIMEOUT);
+    let client = Client::try_from(config)?;
+    Ok(client.apiserver_version().await?.git_version)
+}
+
+/// Reads the credentials of the context from the kubeconfig again,
+/// which runs its exec plugin or picks up the tokens its login tool wrote
+///
+/// # Returns
+/// The new config, once the cluster accepted its credentials
+#[tracing::instrument(
+    skip_all,
+    fields(context = %context.get_name()),
+    err
+)]
+pub async fn reauthenticate(
+    context: KubeContext,
+    kubeconfig_files: Vec<PathBuf>,
+) -> Result<Config, Error> {
+    let config = load_config(&context.get_name(), &kubeconfig_files).await?;
+    let client = Client::try_from(config.clone())?;
+    // the version endpoint is usually readable without credentials, so something that needs them is listed.
+    // being forbidden from listing is fine, since that means the credentials were accepted.
+    let namespaces: Api<Namespace> = Api::all(client);
+    match namespaces.list(&ListParams::default().limit(1)).await {
+        Ok(..) => Ok(config),
+        Err(error) => match Error::from(error) {
+            Error::Forbidden { .. } => Ok(config),
+            error => Err(error),
+        },
+    }
+}
+
+/// Deletes the given `cluster_object` from the cluster given by `context`
+///
+/// Takes a snapshot of the object first, so that the delete can be undone.
+///
+/// # Returns
+/// The snapshot, or `None` if it couldn't be taken or undoing wouldn't make sense,
+/// or an error if the cluster object couldn't be deleted.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+        propagation = %propagation_policy,
+    ),
+    err
+)]
+pub async fn delete(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    propagation_policy: DeletePropagation,
+) -> Result<Option<DynamicObject>, Error> {
+    // failing to take the snapshot shouldn't stop the delete, it just can't be undone
+    let snapshot = snapshot(&context, &cluster_object)
+        .await
+        .inspect_err(|error| tracing::warn!("Unable to take a snapshot: {}", error))
+        .ok()
+        .flatten();
+    delete_without_snapshot(context, cluster_object, propagation_policy).await?;
+    Ok(snapshot)
+}
+
+/// Deletes the given `cluster_object` from the cluster given by `context`,
+/// without taking a snapshot first, so it can't be undone.
+/// Batch deletes use this, since they don't offer undo and the extra request per object would slow them down.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+        propagation = %propagation_policy,
+    ),
+    err
+)]
+pub async fn delete_without_snapshot(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    propagation_policy: DeletePropagation,
+) -> Result<(), Error> {
+    let client = context.get_client()?;
+    let delete_params = DeleteParams {
+        propagation_policy: Some(propagation_policy.to_kube()),
+        ..Default::default()
+    };
 
     match cluster_object.r#type {
         ResourceType::Pod => {
-            let api: Api<Pod> = Api::namespaced(client, &context.get_namespace());
-            let delete_params = DeleteParams::default();
+            let api: Api<Pod> = Api::namespaced(client, &cluster_object.namespace);
             let _ = api
                 .delete(cluster_object.name.as_str(), &delete_params)
                 .await?;
         }
         ResourceType::DaemonSet => {
-            let api: Api<DaemonSet> = Api::namespaced(client, &context.get_namespace());
-            let delete_params = DeleteParams::default();
+            let api: Api<DaemonSet> = Api::namespaced(client, &cluster_object.namespace);
             let _ = api
                 .delete(cluster_object.name.as_str(), &delete_params)
                 .await?;
         }
         ResourceType::Deployment => {
-            let api: Api<Deployment> = Api::namespaced(client, &context.get_namespace());
-            let delete_params = DeleteParams::default();
+            let api: Api<Deployment> = Api::namespaced(client, &cluster_object.namespace);
             let _ = api
                 .delete(cluster_object.name.as_str(), &delete_params)
                 .await?;
         }
         ResourceType::ReplicaSet => {
-            let api: Api<ReplicaSet> = Api::namespaced(client, &context.get_namespace());
-            let delete_params = DeleteParams::default();
+            let api: Api<ReplicaSet> = Api::namespaced(client, &cluster_object.namespace);
             let _ = api
                 .delete(cluster_object.name.as_str(), &delete_params)
                 .await?;
         }
         ResourceType::StatefulSet => {
-            let api: Api<StatefulSet> = Api::namespaced(client, &context.get_namespace());
-            let delete_params = DeleteParams::default();
+            let api: Api<StatefulSet> = Api::namespaced(client, &cluster_object.namespace);
             let _ = api
                 .delete(cluster_object.name.as_str(), &delete_params)
                 .await?;
         }
     };
-    Ok(cluster_object)
+    Ok(())
+}
+
+/// Number of lines from the end of the log that are fetched when viewing the logs of a pod
+const LOG_TAIL_LINES: i64 = 1000;
+
+/// # Returns
+/// The last lines of the log of the pod named `pod_name`, or an error if the log couldn't be read.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %context.get_namespace(),
+        pod = %pod_name,
+    ),
+    err
+)]
+pub async fn fetch_pod_logs(context: KubeContext, pod_name: String) -> Result<String, Error> {
+    let client = context.get_client()?;
+    let pods: Api<Pod> = Api::namespaced(client, &context.get_namespace());
+    let log_params = LogParams {
+        tail_lines: Some(LOG_TAIL_LINES),
+        ..Default::default()
+    };
+    Ok(pods.logs(&pod_name, &log_params).await?)
+}
+
+/// # Returns
+/// The given `cluster_object` as it currently is in the cluster, serialized as YAML,
+/// or an error if it couldn't be fetched.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+    ),
+    err
+)]
+pub async fn fetch_yaml(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+) -> Result<String, Error> {
+    let client = context.get_client()?;
+    let api: Api<DynamicObject> = Api::namespaced_with(
+        client,
+        &cluster_object.namespace,
+        &cluster_object.r#type.api_resource(),
+    );
+    let mut object = api.get(&cluster_object.name).await?;
+    // managed fields are noise for someone editing the object by hand
+    object.metadata.managed_fields = None;
+    Ok(serde_yaml::to_string(&object)?)
+}
+
+/// Replaces the given `cluster_object` in the cluster with the object described by `yaml`
+///
+/// # Returns
+/// An empty result if the object was replaced, or an error if it couldn't be replaced.
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+    ),
+    err
+)]
+pub async fn replace_yaml(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    yaml: String,
+) -> Result<(), Error> {
+    let client = context.get_client()?;
+    let api: Api<DynamicObject> = Api::namespaced_with(
+        client,
+        &cluster_object.namespace,
+        &cluster_object.r#type.api_resource(),
+    );
+    let object: DynamicObject = serde_yaml::from_str(&yaml)?;
+    api.replace(&cluster_object.name, &PostParams::default(), &object)
+        .await?;
+    Ok(())
+}
+
+/// Restarts the pods of the object by changing an annotation of its pod template,
+/// the same way `kubectl rollout restart` does
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+    ),
+    err
+)]
+pub async fn restart(context: KubeContext, cluster_object: ClusterObject) -> Result<(), Error> {
+    if !cluster_object.r#type.can_restart() {
+        return Err(Error::new(format!(
+            "{}s can't be restarted",
+            cluster_object.r#type
+        )));
+    }
+    let patch = serde_json::json!({
+        "spec": {
+            "template": {
+                "metadata": {
+                    "annotations": {
+                        "kubectl.kubernetes.io/restartedAt": chrono::Utc::now().to_rfc3339()
+                    }
+                }
+            }
+        }
+    });
+    merge_patch(&context, &cluster_object, patch).await
+}
+
+/// Changes the number of replicas of the object
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+        replicas,
+    ),
+    err
+)]
+pub async fn scale(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    replicas: i32,
+) -> Result<(), Error> {
+    if !cluster_object.r#type.can_scale() {
+        return Err(Error::new(format!(
+            "{}s can't be scaled",
+            cluster_object.r#type
+        )));
+    }
+    let patch = serde_json::json!({ "spec": { "replicas": replicas } });
+    merge_patch(&context, &cluster_object, patch).await
 }
 
+/// # Returns
+/// The labels and annotations of the object
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+    ),
+    err
+)]
+pub async fn fetch_metadata(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+) -> Result<Metadata, Error> {
+    let client = context.get_client()?;
+    let api: Api<DynamicObject> = Api::namespaced_with(
+        client,
+        &cluster_object.namespace,
+        &cluster_object.r#type.api_resource(),
+    );
+    let object = api.get(&cluster_object.name).await?;
+    Ok(Metadata {
+        labels: object.metadata.labels.unwrap_or_default(),
+        annotations: object.metadata.annotations.unwrap_or_default(),
+    })
+}
+
+/// Sets and removes labels and annotations on the object
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+    ),
+    err
+)]
+pub async fn patch_metadata(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    patch: MetadataPatch,
+) -> Result<(), Error> {
+    let patch = serde_json::json!({ "metadata": patch });
+    merge_patch(&context, &cluster_object, patch).await
+}
+
+/// Applies a JSON merge patch to the object
+async fn merge_patch(
+    context: &KubeContext,
+    cluster_object: &ClusterObject,
+    patch: serde_json::Value,
+) -> Result<(), Error> {
+    let client = context.get_client()?;
+    let api: Api<DynamicObject> = Api::namespaced_with(
+        client,
+        &cluster_object.namespace,
+        &cluster_object.r#type.api_resource(),
+    );
+    api.patch(
+        &cluster_object.name,
+        &PatchParams::default(),
+        &Patch::Merge(patch),
+    )
+    .await?;
+    Ok(())
+}
+
+/// # Returns
+/// The object with the fields that the API server manages stripped, so that it can be created again,
+/// or `None` if it's owned by another object, since its owner creates it again by itself
+async fn snapshot(
+    context: &KubeContext,
+    cluster_object: &ClusterObject,
+) -> Result<Option<DynamicObject>, Error> {
+    let client = context.get_client()?;
+    let api: Api<DynamicObject> = Api::namespaced_with(
+        client,
+        &cluster_object.namespace,
+        &cluster_object.r#type.api_resource(),
+    );
+    let mut object = api.get(&cluster_object.name).await?;
+    if object
+        .metadata
+        .owner_references
+        .as_ref()
+        .is_some_and(|owners| !owners.is_empty())
+    {
+        return Ok(None);
+    }
+    object.metadata = ObjectMeta {
+        name: object.metadata.name,
+        namespace: object.metadata.namespace,
+        labels: object.metadata.labels,
+        annotations: object.metadata.annotations,
+        finalizers: object.metadata.finalizers,
+        ..Default::default()
+    };
+    if let Some(data) = object.data.as_object_mut() {
+        data.remove("status");
+        // the node may be gone by the time it's recreated, so let the scheduler pick again
+        if let Some(spec) = data.get_mut("spec").and_then(|spec| spec.as_object_mut()) {
+            spec.remove("nodeName");
+        }
+    }
+    Ok(Some(object))
+}
+
+/// Creates an object again from a snapshot that was taken before it was deleted
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %cluster_object.namespace,
+        name = %cluster_object.name,
+    ),
+    err
+)]
+pub async fn recreate(
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    snapshot: DynamicObject,
+) -> Result<(), Error> {
+    let client = context.get_client()?;
+    let api: Api<DynamicObject> = Api::namespaced_with(
+        client,
+        &cluster_object.namespace,
+        &cluster_object.r#type.api_resource(),
+    );
+    api.create(&PostParams::default(), &snapshot).await?;
+    Ok(())
+}
+
+/// The namespace is accessible if the user may list any of the resource types in it,
+/// since the ones they may not list are shown as "not permitted".
+///
 /// # Returns
 /// An empty result if the namespace given by `context` is accessible,
 /// or an error if it's not accessible
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %context.get_namespace(),
+    ),
+    err
+)]
 pub async fn check_namespace_accessible(context: KubeContext) -> Result<(), Error> {
-    let client = Client::try_from(context.get_config().to_owned())?;
-    let pods: Api<Pod> = Api::namespaced(client.clone(), &context.get_namespace());
-    pods.list(&ListParams::default()).await?;
-    Ok(())
+    let rules = match fetch_rules(context.clone()).await {
+        Ok(rules) => rules,
+        // every type is tried when the rules can't be reviewed, like when listing
+        Err(Error::Forbidden { .. }) => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    let listable = ResourceType::ALL.iter().any(|r#type| {
+        let api_resource = r#type.api_resource();
+        // the rules might be incomplete, in which case listing is tried anyway
+        rules.allows("list", &api_resource.group, &api_resource.plural, None) != Some(false)
+    });
+    if listable {
+        return Ok(());
+    }
+    let resource = format!("workloads in the namespace {}", context.get_namespace());
+    Err(Error::Forbidden {
+        verb: Some(String::from("list")),
+        status: ApiStatus {
+            code: 403,
+            reason: String::from("Forbidden"),
+            message: format!("The rules don't allow listing any {}", resource),
+        },
+        resource: Some(resource),
+    })
 }
 
-async fn check_cluster_accessible(context: KubeContext) -> Result<(), Error> {
-    let client = Client::try_from(context.get_config().to_owned())?;
+/// # Returns
+/// An empty result if the API server of the cluster given by `context` responds,
+/// or an error if it can't be reached
+#[tracing::instrument(
+    skip_all,
+    fields(
+        context = %context.get_name(),
+        namespace = %context.get_namespace(),
+    ),
+    err
+)]
+pub async fn check_cluster_accessible(context: KubeContext) -> Result<(), Error> {
+    let client = context.get_client()?;
     client.apiserver_version().await?;
     Ok(())
 }
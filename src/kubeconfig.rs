@@ -0,0 +1,803 @@
+//! Reading contexts from several kubeconfig files at once, the same way `kubectl` does, and changing them
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use kube::config::Kubeconfig;
+use serde_yaml::{Mapping, Value};
+
+use crate::error::Error;
+
+const KUBECONFIG: &str = "KUBECONFIG";
+
+/// A context, along with the kubeconfig file it was read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextEntry {
+    pub name: String,
+    pub source: PathBuf,
+    /// Address of the API server of the context's cluster
+    pub server: Option<String>,
+    pub user: Option<String>,
+    /// Namespace that's used when the context is connected to
+    pub namespace: Option<String>,
+    /// Whether this is the `current-context` of the kubeconfig
+    pub is_current: bool,
+    /// Whether the user's credentials come from running a command, i.e. an exec plugin or auth provider,
+    /// which can be slow or ask the user to log in
+    pub runs_command: bool,
+}
+
+/// Every kubeconfig file merged into one, along with where each context came from
+#[derive(Debug, Clone)]
+pub struct MergedKubeconfig {
+    pub kubeconfig: Kubeconfig,
+    pub contexts: Vec<ContextEntry>,
+}
+
+/// # Returns
+/// The kubeconfig files to read, in order of priority.
+/// These are `kubeconfig` if it's given with `--kubeconfig`, otherwise the entries of `$KUBECONFIG`,
+/// or `~/.kube/config` if that isn't set either. They're followed by `extra_files`.
+pub fn kubeconfig_paths(kubeconfig: Option<&Path>, extra_files: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match (kubeconfig, env::var_os(KUBECONFIG)) {
+        (Some(kubeconfig), _) => vec![kubeconfig.to_path_buf()],
+        (None, Some(value)) if !value.is_empty() => env::split_paths(&value)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect(),
+        _ => dirs::home_dir()
+            .map(|home| home.join(".kube").join("config"))
+            .into_iter()
+            .collect(),
+    };
+    paths.extend(extra_files.iter().cloned());
+
+    // the same file can be listed twice under different spellings, e.g. with `..` or through a symlink,
+    // so they're compared by the file they lead to. Files that don't exist are compared as they're written.
+    let mut seen: Vec<PathBuf> = vec![];
+    paths.retain(|path| {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        let is_new = !seen.contains(&canonical);
+        seen.push(canonical);
+        is_new
+    });
+    paths
+}
+
+/// Reads and merges the kubeconfig files at `paths`, which come from `kubeconfig_paths`.
+///
+/// When the same context, cluster or user is in several files, the first one wins, like it does in `kubectl`.
+/// Files that don't exist are skipped, since `$KUBECONFIG` often lists files that only exist on some machines.
+pub fn read_merged(paths: &[PathBuf]) -> Result<MergedKubeconfig, Error> {
+    let mut merged = Kubeconfig::default();
+    let mut contexts: Vec<ContextEntry> = vec![];
+    let mut found_any = false;
+
+    for path in paths {
+        if !path.exists() {
+            tracing::debug!("Skipping missing kubeconfig {}", path.display());
+            continue;
+        }
+        found_any = true;
+        let kubeconfig = Kubeconfig::read_from(path)?;
+        for context in &kubeconfig.contexts {
+            if !contexts.iter().any(|entry| entry.name == context.name) {
+                contexts.push(ContextEntry {
+                    name: context.name.clone(),
+                    source: path.to_path_buf(),
+                    server: None,
+                    user: None,
+                    namespace: None,
+                    is_current: false,
+                    runs_command: false,
+                });
+            }
+        }
+        merged = merged.merge(kubeconfig)?;
+    }
+
+    // the cluster and user of a context can be defined in a different file than the context itself,
+    // so they're looked up once everything has been merged
+    for entry in contexts.iter_mut() {
+        let context = merged
+            .contexts
+            .iter()
+            .find(|context| context.name == entry.name)
+            .and_then(|context| context.context.as_ref());
+        if let Some(context) = context {
+            entry.server = merged
+                .clusters
+                .iter()
+                .find(|cluster| cluster.name == context.cluster)
+                .and_then(|cluster| cluster.cluster.as_ref())
+                .and_then(|cluster| cluster.server.clone());
+            entry.user = Some(context.user.clone());
+            entry.namespace = context.namespace.clone();
+            entry.runs_command = merged
+                .auth_infos
+                .iter()
+                .find(|user| user.name == context.user)
+                .and_then(|user| user.auth_info.as_ref())
+                .is_some_and(|user| user.exec.is_some() || user.auth_provider.is_some());
+        }
+        entry.is_current = merged.current_context.as_ref() == Some(&entry.name);
+    }
+
+    if !found_any {
+        return Err(Error::Config {
+            message: String::from("None of the kubeconfig files exist"),
+        });
+    }
+    Ok(MergedKubeconfig {
+        kubeconfig: merged,
+        contexts,
+    })
+}
+
+/// Expands a leading `~` to the user's home directory, since that's how most people write paths
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// # Returns
+/// The absolute path of the given kubeconfig file, or an error if it isn't a valid kubeconfig
+pub async fn check_file(path: PathBuf) -> Result<PathBuf, Error> {
+    blocking(move || {
+        let path = fs::canonicalize(path)?;
+        Kubeconfig::read_from(&path)?;
+        Ok(path)
+    })
+    .await
+}
+
+/// Runs `work` on a thread where blocking is fine, since reading and writing files blocks,
+/// which would hold up the threads that run the rest of the app's futures
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|error| Error::new(error.to_string()))?
+}
+
+/// Makes the context named `name` the `current-context`, like `kubectl config use-context` does.
+///
+/// It's written to the first file that already sets `current-context`, or the first file if none of them do.
+///
+/// # Returns
+/// The file that was changed
+pub async fn use_context(name: String, paths: Vec<PathBuf>) -> Result<PathBuf, Error> {
+    blocking(move || {
+        let paths: Vec<PathBuf> = paths.into_iter().filter(|path| path.exists()).collect();
+        let path = paths
+            .iter()
+            .find(|path| {
+                Kubeconfig::read_from(path)
+                    .is_ok_and(|kubeconfig| kubeconfig.current_context.is_some())
+            })
+            .or(paths.first())
+            .cloned()
+            .ok_or_else(|| Error::Config {
+                message: String::from("None of the kubeconfig files exist"),
+            })?;
+
+        edit_file(&path, |document| {
+            document.insert("current-context".into(), name.into());
+            Ok(())
+        })?;
+        Ok(path)
+    })
+    .await
+}
+
+/// Sets the namespace that the context named `name` uses by default,
+/// like `kubectl config set-context --current --namespace` does.
+///
+/// # Returns
+/// The file that was changed, which is the one that defines the context
+pub async fn set_context_namespace(
+    name: String,
+    namespace: String,
+    paths: Vec<PathBuf>,
+) -> Result<PathBuf, Error> {
+    blocking(move || set_context_namespace_blocking(&name, namespace, &paths)).await
+}
+
+fn set_context_namespace_blocking(
+    name: &str,
+    namespace: String,
+    paths: &[PathBuf],
+) -> Result<PathBuf, Error> {
+    let path = read_merged(paths)?
+        .contexts
+        .into_iter()
+        .find(|context| context.name == name)
+        .map(|context| context.source)
+        .ok_or_else(|| Error::Config {
+            message: format!("The context {} isn't in any of the kubeconfig files", name),
+        })?;
+
+    edit_file(&path, |document| {
+        let context = document
+            .get_mut("contexts")
+            .and_then(Value::as_sequence_mut)
+            .and_then(|contexts| {
+                contexts
+                    .iter_mut()
+                    .find(|context| context.get("name").and_then(Value::as_str) == Some(name))
+            })
+            .and_then(|context| context.get_mut("context"))
+            .and_then(Value::as_mapping_mut)
+            .ok_or_else(|| Error::Config {
+                message: format!("The context {} is malformed", name),
+            })?;
+        context.insert("namespace".into(), namespace.into());
+        Ok(())
+    })?;
+    Ok(path)
+}
+
+/// Changes a kubeconfig file through `edit`.
+///
+/// The file is edited as plain YAML rather than as a `Kubeconfig`,
+/// so that fields kube doesn't know about are kept as they are. Comments aren't kept though.
+///
+/// # Returns
+/// What `edit` returned, and the path of the backup
+fn edit_file<T>(
+    path: &Path,
+    edit: impl FnOnce(&mut Mapping) -> Result<T, Error>,
+) -> Result<(T, PathBuf), Error> {
+    let mut document: Value = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+    let mapping = document.as_mapping_mut().ok_or_else(|| Error::Config {
+        message: format!("{} isn't a kubeconfig file", path.display()),
+    })?;
+    let edited = edit(mapping)?;
+
+    let backup = write_file(path, &serde_yaml::to_string(&document)?)?;
+    Ok((edited, backup))
+}
+
+/// Replaces the contents of a kubeconfig file, keeping a copy of the old contents next to it.
+///
+/// The new file is written next to the old one and then moved over it,
+/// so that the file is never left half written.
+/// Symlinks are followed first, so that the file they point to is replaced rather than the link.
+///
+/// # Returns
+/// The path of the backup
+fn write_file(path: &Path, contents: &str) -> Result<PathBuf, Error> {
+    let path = &fs::canonicalize(path)?;
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(format!(
+        ".{}.bak",
+        chrono::Local::now().format("%Y-%m-%dT%H-%M-%S")
+    ));
+    let backup_path = PathBuf::from(backup_path);
+    // copying keeps the permissions, which matters since kubeconfigs usually hold credentials
+    fs::copy(path, &backup_path)?;
+
+    let permissions = fs::metadata(path)?.permissions();
+    let mut temp_path = path.as_os_str().to_owned();
+    // unique, so that two writers can't write to the same temporary file
+    temp_path.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_nanos())
+            .unwrap_or_default()
+    ));
+    let temp_path = PathBuf::from(temp_path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // created with the permissions of the old file, so the credentials are never readable by anyone else
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(permissions.mode());
+    }
+    let written = options
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        // the umask can take permissions away when the file is created
+        .and_then(|()| fs::set_permissions(&temp_path, permissions))
+        .and_then(|()| fs::rename(&temp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written?;
+    Ok(backup_path)
+}
+
+/// Reads each of the kubeconfig files that exist, without merging them
+pub async fn read_files(paths: Vec<PathBuf>) -> Result<Vec<(PathBuf, Kubeconfig)>, Error> {
+    blocking(move || {
+        paths
+            .into_iter()
+            .filter(|path| path.exists())
+            .map(|path| {
+                let kubeconfig = Kubeconfig::read_from(&path)?;
+                Ok((path, kubeconfig))
+            })
+            .collect()
+    })
+    .await
+}
+
+/// A kind of entry in a kubeconfig file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Context,
+    Cluster,
+    User,
+}
+
+impl std::fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Context => write!(f, "context"),
+            Self::Cluster => write!(f, "cluster"),
+            Self::User => write!(f, "user"),
+        }
+    }
+}
+
+/// A change to a single kubeconfig file
+#[derive(Debug, Clone)]
+pub enum KubeconfigEdit {
+    /// Renames an entry, along with the contexts that refer to it
+    Rename {
+        kind: EntryKind,
+        from: String,
+        to: String,
+    },
+    /// Deletes an entry. Clusters and users that a context still uses can't be deleted.
+    Delete(EntryKind, String),
+    /// Adds the contexts, clusters and users of another kubeconfig file,
+    /// except for those whose names are already used
+    Import(PathBuf),
+    /// Deletes the contexts, along with their clusters and users if no other context uses them
+    Prune(Vec<String>),
+}
+
+impl EntryKind {
+    /// The key of the list of entries of this kind in a kubeconfig file
+    fn key(self) -> &'static str {
+        match self {
+            Self::Context => "contexts",
+            Self::Cluster => "clusters",
+            Self::User => "users",
+        }
+    }
+}
+
+impl KubeconfigEdit {
+    /// Makes the change to the YAML of a kubeconfig file
+    ///
+    /// # Returns
+    /// The entries that were left out because their names were already used, as "kind name"
+    fn apply(self, document: &mut Mapping) -> Result<Vec<String>, Error> {
+        let mut skipped = vec![];
+        match self {
+            KubeconfigEdit::Rename { kind, from, to } => {
+                let to = to.trim().to_owned();
+                if to.is_empty() {
+                    return Err(Error::new(format!("The new {} name can't be empty", kind)));
+                }
+                if names(document, kind).contains(&to) {
+                    return Err(Error::new(format!(
+                        "A {} called {} already exists",
+                        kind, to
+                    )));
+                }
+                for entry in entries_mut(document, kind)? {
+                    if name_of(entry) == Some(&from) {
+                        set(entry, "name", &to);
+                    }
+                }
+                match kind {
+                    EntryKind::Context => {
+                        if document.get("current-context").and_then(Value::as_str) == Some(&from) {
+                            document.insert("current-context".into(), to.into());
+                        }
+                    }
+                    EntryKind::Cluster | EntryKind::User => {
+                        for context in entries_mut(document, EntryKind::Context)? {
+                            if let Some(context) = context.get_mut("context") {
+                                if context.get(kind.to_string()).and_then(Value::as_str)
+                                    == Some(&from)
+                                {
+                                    set(context, &kind.to_string(), &to);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            KubeconfigEdit::Delete(kind, name) => {
+                if kind != EntryKind::Context {
+                    let used_by = contexts_using(document, kind, &name);
+                    if !used_by.is_empty() {
+                        return Err(Error::new(format!(
+                            "The {} {} is used by {}. Delete those contexts first.",
+                            kind,
+                            name,
+                            used_by.join(", ")
+                        )));
+                    }
+                }
+                entries_mut(document, kind)?.retain(|entry| name_of(entry) != Some(&name));
+                if kind == EntryKind::Context
+                    && document.get("current-context").and_then(Value::as_str) == Some(&name)
+                {
+                    document.remove("current-context");
+                }
+            }
+            KubeconfigEdit::Import(path) => {
+                let imported: Value = serde_yaml::from_str(&fs::read_to_string(&path)?)?;
+                // the current context of the imported file is left out, since importing shouldn't change it
+                for kind in [EntryKind::Context, EntryKind::Cluster, EntryKind::User] {
+                    let Some(imported_entries) =
+                        imported.get(kind.key()).and_then(Value::as_sequence)
+                    else {
+                        continue;
+                    };
+                    let existing = names(document, kind);
+                    let entries = entries_mut(document, kind)?;
+                    for entry in imported_entries {
+                        match name_of(entry) {
+                            Some(name) if existing.iter().any(|existing| existing == name) => {
+                                skipped.push(format!("{} {}", kind, name));
+                            }
+                            _ => entries.push(entry.clone()),
+                        }
+                    }
+                }
+            }
+            KubeconfigEdit::Prune(pruned) => {
+                let is_pruned = |context: &Value| {
+                    name_of(context).is_some_and(|name| pruned.iter().any(|pruned| pruned == name))
+                };
+                for kind in [EntryKind::Cluster, EntryKind::User] {
+                    let field = kind.to_string();
+                    let references = |prune: bool| -> Vec<String> {
+                        document
+                            .get(EntryKind::Context.key())
+                            .and_then(Value::as_sequence)
+                            .into_iter()
+                            .flatten()
+                            .filter(|context| is_pruned(context) == prune)
+                            .filter_map(|context| context.get("context")?.get(&field)?.as_str())
+                            .map(str::to_owned)
+                            .collect()
+                    };
+                    let kept = references(false);
+                    let unused: Vec<String> = references(true)
+                        .into_iter()
+                        .filter(|name| !kept.contains(name))
+                        .collect();
+                    entries_mut(document, kind)?.retain(|entry| {
+                        !name_of(entry)
+                            .is_some_and(|name| unused.iter().any(|unused| unused == name))
+                    });
+                }
+                entries_mut(document, EntryKind::Context)?.retain(|context| !is_pruned(context));
+                if document
+                    .get("current-context")
+                    .and_then(Value::as_str)
+                    .is_some_and(|current| pruned.iter().any(|pruned| pruned == current))
+                {
+                    document.remove("current-context");
+                }
+            }
+        }
+        Ok(skipped)
+    }
+}
+
+fn name_of(entry: &Value) -> Option<&str> {
+    entry.get("name").and_then(Value::as_str)
+}
+
+fn set(entry: &mut Value, key: &str, value: &str) {
+    if let Some(entry) = entry.as_mapping_mut() {
+        entry.insert(key.into(), value.into());
+    }
+}
+
+/// # Returns
+/// The names of the entries of the given kind in the YAML of a kubeconfig file
+fn names(document: &Mapping, kind: EntryKind) -> Vec<String> {
+    document
+        .get(kind.key())
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(name_of)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// # Returns
+/// The entries of the given kind in the YAML of a kubeconfig file, which are added if the file doesn't have any
+fn entries_mut(document: &mut Mapping, kind: EntryKind) -> Result<&mut Vec<Value>, Error> {
+    let entries = document.entry(kind.key().into()).or_insert(Value::Null);
+    if entries.is_null() {
+        *entries = Value::Sequence(vec![]);
+    }
+    entries.as_sequence_mut().ok_or_else(|| Error::Config {
+        message: format!("The {}s in the kubeconfig aren't a list", kind),
+    })
+}
+
+/// # Returns
+/// The names of the contexts that use the cluster or user called `name`
+fn contexts_using(document: &Mapping, kind: EntryKind, name: &str) -> Vec<String> {
+    let field = kind.to_string();
+    document
+        .get(EntryKind::Context.key())
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter(|context| {
+            context
+                .get("context")
+                .and_then(|context| context.get(&field))
+                .and_then(Value::as_str)
+                == Some(name)
+        })
+        .filter_map(name_of)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// # Returns
+/// The names of the entries of the given kind
+pub fn entry_names(
+    kubeconfig: &Kubeconfig,
+    kind: EntryKind,
+) -> Box<dyn Iterator<Item = &String> + '_> {
+    match kind {
+        EntryKind::Context => Box::new(kubeconfig.contexts.iter().map(|context| &context.name)),
+        EntryKind::Cluster => Box::new(kubeconfig.clusters.iter().map(|cluster| &cluster.name)),
+        EntryKind::User => Box::new(kubeconfig.auth_infos.iter().map(|user| &user.name)),
+    }
+}
+
+/// What a change to a kubeconfig file did
+#[derive(Debug, Clone)]
+pub struct Edited {
+    /// The new contents of the file
+    pub kubeconfig: Kubeconfig,
+    pub backup: PathBuf,
+    /// The entries that were left out of an import because their names were already used, as "kind name"
+    pub skipped: Vec<String>,
+}
+
+/// Makes a change to a kubeconfig file.
+///
+/// The file is read again rather than using what's on screen, so that changes made by other tools aren't lost.
+/// Like the other changes, it's made to the YAML rather than through kube's `Kubeconfig`,
+/// so that fields kube doesn't know about are kept.
+pub async fn edit(path: PathBuf, edit: KubeconfigEdit) -> Result<Edited, Error> {
+    blocking(move || {
+        let (skipped, backup) = edit_file(&path, |document| edit.apply(document))?;
+        Ok(Edited {
+            kubeconfig: Kubeconfig::read_from(&path)?,
+            backup,
+            skipped,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_the_same_file_once_under_different_spellings() {
+        let dir = env::temp_dir().join(format!("iced-k8s-kubeconfig-paths-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        let config = dir.join("config");
+        fs::write(&config, "").unwrap();
+
+        let paths = kubeconfig_paths(
+            Some(&config),
+            &[dir.join("nested").join("..").join("config")],
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths, vec![config]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_through_symlinks() {
+        let dir = env::temp_dir().join(format!("iced-k8s-write-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config");
+        let link = dir.join("link");
+        fs::write(&config, "old").unwrap();
+        std::os::unix::fs::symlink(&config, &link).unwrap();
+
+        let backup = write_file(&link, "new").unwrap();
+        let is_symlink = fs::symlink_metadata(&link).unwrap().is_symlink();
+        let contents = fs::read_to_string(&config).unwrap();
+        let backup_name = backup.file_name().unwrap().to_string_lossy().into_owned();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(is_symlink);
+        assert_eq!(contents, "new");
+        // the backup is of the file the link points to
+        assert!(backup_name.starts_with("config."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_the_permissions_of_the_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = env::temp_dir().join(format!("iced-k8s-permissions-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("config");
+        fs::write(&config, "old").unwrap();
+        fs::set_permissions(&config, fs::Permissions::from_mode(0o600)).unwrap();
+
+        write_file(&config, "new").unwrap();
+        let mode = fs::metadata(&config).unwrap().permissions().mode() & 0o777;
+        let leftovers = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(mode, 0o600);
+        assert_eq!(leftovers, 0);
+    }
+
+    const KUBECONFIG: &str = "
+apiVersion: v1
+kind: Config
+current-context: old
+preferences:
+  colors: true
+contexts:
+- name: old
+  context:
+    cluster: shared
+    user: old-user
+    extensions:
+    - name: kept
+      extension: {}
+- name: new
+  context:
+    cluster: shared
+    user: new-user
+clusters:
+- name: shared
+  cluster:
+    server: https://example.com
+users:
+- name: old-user
+  user:
+    token: abc
+- name: new-user
+  user:
+    token: def
+";
+
+    fn document() -> Mapping {
+        serde_yaml::from_str(KUBECONFIG).unwrap()
+    }
+
+    #[test]
+    fn renames_clusters_where_contexts_use_them() {
+        let mut document = document();
+
+        KubeconfigEdit::Rename {
+            kind: EntryKind::Cluster,
+            from: String::from("shared"),
+            to: String::from("renamed"),
+        }
+        .apply(&mut document)
+        .unwrap();
+
+        assert_eq!(names(&document, EntryKind::Cluster), vec!["renamed"]);
+        assert_eq!(
+            contexts_using(&document, EntryKind::Cluster, "renamed"),
+            vec!["old", "new"]
+        );
+        // fields kube doesn't know about are kept
+        assert!(document["preferences"]["colors"].as_bool().unwrap());
+        assert!(document["contexts"][0]["context"]["extensions"].is_sequence());
+    }
+
+    #[test]
+    fn refuses_to_delete_users_that_contexts_use() {
+        let mut document = document();
+
+        let error = KubeconfigEdit::Delete(EntryKind::User, String::from("old-user"))
+            .apply(&mut document)
+            .unwrap_err();
+
+        assert!(error.get_message().contains("old"));
+        assert_eq!(
+            names(&document, EntryKind::User),
+            vec!["old-user", "new-user"]
+        );
+    }
+
+    #[test]
+    fn deleting_the_current_context_unsets_it() {
+        let mut document = document();
+
+        KubeconfigEdit::Delete(EntryKind::Context, String::from("old"))
+            .apply(&mut document)
+            .unwrap();
+
+        assert_eq!(names(&document, EntryKind::Context), vec!["new"]);
+        assert!(document.get("current-context").is_none());
+    }
+
+    #[test]
+    fn prunes_the_clusters_and_users_only_pruned_contexts_use() {
+        let mut document = document();
+
+        KubeconfigEdit::Prune(vec![String::from("old")])
+            .apply(&mut document)
+            .unwrap();
+
+        assert_eq!(names(&document, EntryKind::Context), vec!["new"]);
+        assert_eq!(names(&document, EntryKind::Cluster), vec!["shared"]);
+        assert_eq!(names(&document, EntryKind::User), vec!["new-user"]);
+    }
+
+    #[test]
+    fn imports_entries_whose_names_are_new() {
+        let path = env::temp_dir().join(format!("iced-k8s-import-{}", std::process::id()));
+        fs::write(
+            &path,
+            "
+current-context: other
+contexts:
+- name: old
+  context: {cluster: other, user: other}
+- name: other
+  context: {cluster: other, user: other}
+clusters:
+- name: other
+  cluster: {server: https://other.example.com}
+",
+        )
+        .unwrap();
+        let mut document = document();
+
+        let skipped = KubeconfigEdit::Import(path.clone())
+            .apply(&mut document)
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(skipped, vec!["context old"]);
+        assert_eq!(
+            names(&document, EntryKind::Context),
+            vec!["old", "new", "other"]
+        );
+        assert_eq!(
+            names(&document, EntryKind::Cluster),
+            vec!["shared", "other"]
+        );
+        assert_eq!(document["current-context"].as_str(), Some("old"));
+    }
+}
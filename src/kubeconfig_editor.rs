@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Utc};
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text, text_input, Column, Row,
+};
+use iced::{Alignment, Command, Element, Length, Padding};
+use kube::config::Kubeconfig;
+
+use crate::button_theme::{as_button_theme, ButtonTheme};
+use crate::colours::Palette;
+use crate::container_theme::{as_container_theme, ContainerTheme};
+use crate::custom_widgets::toast::{Status, Toast};
+use crate::kubeconfig::{self, EntryKind, KubeconfigEdit};
+use crate::messages::{KubeconfigEditorMessage, Message};
+use crate::sizes;
+use crate::utils;
+
+/// How long a context's cluster has to be unreachable before it's offered for removal
+const STALE_AFTER_DAYS: i64 = 30;
+
+/// The entry that's being renamed
+#[derive(Debug, Clone)]
+struct Renaming {
+    kind: EntryKind,
+    name: String,
+}
+
+/// Screen for cleaning up the contexts, clusters and users of the kubeconfig files
+#[derive(Debug, Clone)]
+pub struct KubeconfigEditor {
+    files: Vec<(PathBuf, Kubeconfig)>,
+    selected_file: Option<PathBuf>,
+    /// When each context was first found to be unreachable, by context name
+    unreachable_since: HashMap<String, DateTime<Utc>>,
+    renaming: Option<Renaming>,
+    rename_field_value: String,
+    import_field_value: String,
+    /// The delete or prune that's waiting to be confirmed
+    pending: Option<KubeconfigEdit>,
+}
+
+impl KubeconfigEditor {
+    pub fn new(
+        files: Vec<(PathBuf, Kubeconfig)>,
+        unreachable_since: HashMap<String, DateTime<Utc>>,
+    ) -> KubeconfigEditor {
+        KubeconfigEditor {
+            selected_file: files.first().map(|(path, _)| path.clone()),
+            files,
+            unreachable_since,
+            renaming: None,
+            rename_field_value: String::from(""),
+            import_field_value: String::from(""),
+            pending: None,
+        }
+    }
+
+    pub fn update(&mut self, message: KubeconfigEditorMessage) -> Command<Message> {
+        match message {
+            KubeconfigEditorMessage::FileSelected(path) => {
+                self.selected_file = Some(path);
+                self.renaming = None;
+                self.pending = None;
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::RenameRequested(kind, name) => {
+                self.rename_field_value = name.clone();
+                self.renaming = Some(Renaming { kind, name });
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::RenameFieldChanged(value) => {
+                self.rename_field_value = value;
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::RenameCancelled => {
+                self.renaming = None;
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::RenameConfirmed => match self.renaming.take() {
+                Some(Renaming { kind, name }) if name != self.rename_field_value.trim() => self
+                    .edit(KubeconfigEdit::Rename {
+                        kind,
+                        from: name,
+                        to: self.rename_field_value.clone(),
+                    }),
+                _ => Command::none(),
+            },
+            KubeconfigEditorMessage::DeleteRequested(kind, name) => {
+                self.pending = Some(KubeconfigEdit::Delete(kind, name));
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::ImportFieldChanged(value) => {
+                self.import_field_value = value;
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::ImportRequested => {
+                if self.import_field_value.trim().is_empty() {
+                    return Command::none();
+                }
+                let path = kubeconfig::expand_home(self.import_field_value.trim());
+                self.import_field_value = String::from("");
+                self.edit(KubeconfigEdit::Import(path))
+            }
+            KubeconfigEditorMessage::PruneRequested => {
+                let stale = self.stale_contexts();
+                if stale.is_empty() {
+                    return Command::none();
+                }
+                self.pending = Some(KubeconfigEdit::Prune(stale));
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::EditConfirmed => match self.pending.take() {
+                Some(edit) => self.edit(edit),
+                None => Command::none(),
+            },
+            KubeconfigEditorMessage::EditCancelled => {
+                self.pending = None;
+
+                Command::none()
+            }
+            KubeconfigEditorMessage::Edited(path, edit, Ok(edited)) => {
+                let mut description = format!(
+                    "{} was saved. The previous version was kept in {}",
+                    path.display(),
+                    edited.backup.display()
+                );
+                if !edited.skipped.is_empty() {
+                    description = format!(
+                        "{}. These weren't imported because their names are already used: {}",
+                        description,
+                        edited.skipped.join(", ")
+                    );
+                }
+                let toast = Toast::new(Status::Success, "Kubeconfig updated", description);
+                if let Some((_, file)) = self
+                    .files
+                    .iter_mut()
+                    .find(|(file_path, _)| *file_path == path)
+                {
+                    *file = edited.kubeconfig;
+                }
+                let mut commands = vec![Command::perform(utils::resolved(), move |_ignored| {
+                    Message::AddToast(toast)
+                })];
+                if let KubeconfigEdit::Rename {
+                    kind: EntryKind::Context,
+                    from,
+                    to,
+                } = edit
+                {
+                    let to = to.trim().to_owned();
+                    if let Some(since) = self.unreachable_since.remove(&from) {
+                        self.unreachable_since.insert(to.clone(), since);
+                    }
+                    commands.push(Command::perform(utils::resolved(), move |_ignored| {
+                        Message::ContextRenamed(from, to)
+                    }));
+                }
+
+                Command::batch(commands)
+            }
+            KubeconfigEditorMessage::Edited(path, _, Err(error)) => {
+                tracing::error!(path = %path.display(), %error, "unable to edit the kubeconfig");
+                let toast = error.to_toast("Unable to change the kubeconfig.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+        }
+    }
+
+    /// Applies the edit to the selected file in the background
+    fn edit(&self, edit: KubeconfigEdit) -> Command<Message> {
+        let Some(path) = self.selected_file.clone() else {
+            return Command::none();
+        };
+        Command::perform(kubeconfig::edit(path.clone(), edit.clone()), move |res| {
+            KubeconfigEditorMessage::Edited(path, edit, res).into()
+        })
+    }
+
+    fn selected_kubeconfig(&self) -> Option<&Kubeconfig> {
+        self.files
+            .iter()
+            .find(|(path, _)| Some(path) == self.selected_file.as_ref())
+            .map(|(_, kubeconfig)| kubeconfig)
+    }
+
+    /// # Returns
+    /// The contexts of the selected file whose cluster has been unreachable for a long time
+    fn stale_contexts(&self) -> Vec<String> {
+        let cutoff = Utc::now() - Duration::days(STALE_AFTER_DAYS);
+        self.selected_kubeconfig()
+            .map(|kubeconfig| {
+                kubeconfig
+                    .contexts
+                    .iter()
+                    .filter(|context| {
+                        self.unreachable_since
+                            .get(&context.name)
+                            .is_some_and(|since| *since <= cutoff)
+                    })
+                    .map(|context| context.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let files: Vec<Element<Message>> = self
+            .files
+            .iter()
+            .map(|(path, _)| {
+                button(text(path.display().to_string()).size(sizes::P))
+                    .on_press(KubeconfigEditorMessage::FileSelected(path.clone()).into())
+                    .style(if Some(path) == self.selected_file.as_ref() {
+                        as_button_theme(ButtonTheme::Secondary)
+                    } else {
+                        iced::theme::Button::Text
+                    })
+                    .into()
+            })
+            .collect();
+
+        let entries: Element<Message> = match self.selected_kubeconfig() {
+            Some(kubeconfig) => scrollable(
+                column![
+                    self.section(kubeconfig, EntryKind::Context, palette),
+                    self.section(kubeconfig, EntryKind::Cluster, palette),
+                    self.section(kubeconfig, EntryKind::User, palette),
+                ]
+                .spacing(sizes::SEP),
+            )
+            .height(Length::Fill)
+            .into(),
+            None => {
+                container(text("None of the kubeconfig files exist").style(palette.secondary_text))
+                    .height(Length::Fill)
+                    .into()
+            }
+        };
+
+        let mut content = column![
+            text("Kubeconfig").size(sizes::H2).style(palette.text),
+            Row::with_children(files).spacing(sizes::SMOL_SEP),
+            entries,
+        ]
+        .max_width(800)
+        .spacing(sizes::SEP);
+
+        let stale = self.stale_contexts();
+        if !stale.is_empty() {
+            content = content.push(
+                row![
+                    text(format!(
+                        "Unreachable for over {} days: {}",
+                        STALE_AFTER_DAYS,
+                        stale.join(", ")
+                    ))
+                    .style(palette.danger)
+                    .width(Length::Fill),
+                    button(container(text("Remove Stale Contexts")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .on_press(KubeconfigEditorMessage::PruneRequested.into())
+                    .style(iced::theme::Button::Destructive),
+                ]
+                .spacing(sizes::SEP)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        if let Some(pending) = &self.pending {
+            content = content.push(self.confirmation(pending, palette));
+        }
+
+        content = content.push(
+            row![
+                text_input(
+                    "Path of a kubeconfig file to import",
+                    &self.import_field_value
+                )
+                .on_input(|value| KubeconfigEditorMessage::ImportFieldChanged(value).into())
+                .on_submit(KubeconfigEditorMessage::ImportRequested.into()),
+                button(container(text("Import")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(KubeconfigEditorMessage::ImportRequested.into())
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        );
+
+        content = content.push(
+            row![
+                text("Comments in the file aren't kept. A backup is kept next to the file each time it's changed")
+                    .size(sizes::P * 3.0 / 4.0)
+                    .style(palette.secondary_text),
+                horizontal_space(Length::Fill),
+                button(container(text("Close")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(Message::KubeconfigEditorClosed)
+                .style(as_button_theme(ButtonTheme::Primary)),
+            ]
+            .align_items(Alignment::Center),
+        );
+
+        container(content)
+            .style(as_container_theme(ContainerTheme::Light))
+            .padding(sizes::SEP)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .into()
+    }
+
+    /// Asks the user to confirm a delete or prune before it's made
+    fn confirmation<'a>(
+        &'a self,
+        pending: &KubeconfigEdit,
+        palette: Palette,
+    ) -> Element<'a, Message> {
+        let path = self
+            .selected_file
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        let (question, action) = match pending {
+            KubeconfigEdit::Delete(kind, name) => (
+                format!(
+                    "Are you sure you want to delete the {} {} from {}?",
+                    kind, name, path
+                ),
+                "Delete",
+            ),
+            KubeconfigEdit::Prune(contexts) => (
+                format!(
+                    "Are you sure you want to remove {} from {}, along with the clusters and users only they use?",
+                    contexts.join(", "),
+                    path
+                ),
+                "Remove",
+            ),
+            KubeconfigEdit::Rename { .. } | KubeconfigEdit::Import(_) => {
+                (String::from(""), "Confirm")
+            }
+        };
+
+        container(
+            row![
+                text(question).style(palette.text),
+                horizontal_space(Length::Fill),
+                button(container(text("Cancel")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(KubeconfigEditorMessage::EditCancelled.into())
+                .style(as_button_theme(ButtonTheme::Secondary)),
+                button(container(text(action)).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(KubeconfigEditorMessage::EditConfirmed.into())
+                .style(iced::theme::Button::Destructive),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(sizes::SEP)
+        .style(as_container_theme(ContainerTheme::Light))
+        .into()
+    }
+
+    /// Lists the entries of one kind, with buttons to rename or delete each of them
+    fn section<'a>(
+        &'a self,
+        kubeconfig: &'a Kubeconfig,
+        kind: EntryKind,
+        palette: Palette,
+    ) -> Element<'a, Message> {
+        let title = match kind {
+            EntryKind::Context => "Contexts",
+            EntryKind::Cluster => "Clusters",
+            EntryKind::User => "Users",
+        };
+        let entries: Vec<Element<Message>> = kubeconfig::entry_names(kubeconfig, kind)
+            .map(|name| self.entry(kubeconfig, kind, name, palette))
+            .collect();
+
+        column![
+            text(title).style(palette.secondary_text),
+            Column::with_children(entries).spacing(sizes::SMOL_SEP),
+        ]
+        .spacing(sizes::SMOL_SEP)
+        .into()
+    }
+
+    fn entry<'a>(
+        &'a self,
+        kubeconfig: &'a Kubeconfig,
+        kind: EntryKind,
+        name: &'a String,
+        palette: Palette,
+    ) -> Element<'a, Message> {
+        let is_renaming = self
+            .renaming
+            .as_ref()
+            .is_some_and(|renaming| renaming.kind == kind && renaming.name == *name);
+        if is_renaming {
+            return row![
+                text_input("New name", &self.rename_field_value)
+                    .on_input(|value| KubeconfigEditorMessage::RenameFieldChanged(value).into())
+                    .on_submit(KubeconfigEditorMessage::RenameConfirmed.into()),
+                button(text("Cancel").size(sizes::P))
+                    .on_press(KubeconfigEditorMessage::RenameCancelled.into())
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                button(text("Save").size(sizes::P))
+                    .on_press(KubeconfigEditorMessage::RenameConfirmed.into())
+                    .style(as_button_theme(ButtonTheme::Primary)),
+            ]
+            .spacing(sizes::SMOL_SEP)
+            .align_items(Alignment::Center)
+            .into();
+        }
+
+        let mut details = column![text(name).style(palette.text)].width(Length::Fill);
+        details = details.push(
+            text(describe(kubeconfig, kind, name))
+                .size(sizes::P * 3.0 / 4.0)
+                .style(palette.secondary_text),
+        );
+        if kind == EntryKind::Context {
+            if let Some(since) = self.unreachable_since.get(name) {
+                details = details.push(
+                    text(format!(
+                        "Unreachable since {}",
+                        since.with_timezone(&chrono::Local).format("%Y-%m-%d")
+                    ))
+                    .size(sizes::P * 3.0 / 4.0)
+                    .style(palette.danger),
+                );
+            }
+        }
+
+        row![
+            details,
+            button(text("Rename").size(sizes::P))
+                .on_press(KubeconfigEditorMessage::RenameRequested(kind, name.clone()).into())
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            button(text("Delete").size(sizes::P))
+                .on_press(KubeconfigEditorMessage::DeleteRequested(kind, name.clone()).into())
+                .style(iced::theme::Button::Destructive),
+        ]
+        .spacing(sizes::SMOL_SEP)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
+
+/// # Returns
+/// What the entry points to, or which contexts use it
+fn describe(kubeconfig: &Kubeconfig, kind: EntryKind, name: &str) -> String {
+    let contexts = || {
+        kubeconfig
+            .contexts
+            .iter()
+            .filter_map(|context| context.context.as_ref().map(|inner| (&context.name, inner)))
+    };
+    let used_by = |names: Vec<&String>| match names.len() {
+        0 => String::from("Not used by any context"),
+        1 => String::from("Used by 1 context"),
+        count => format!("Used by {} contexts", count),
+    };
+    match kind {
+        EntryKind::Context => contexts()
+            .find(|(context_name, _)| *context_name == name)
+            .map(|(_, context)| format!("cluster: {}  ·  user: {}", context.cluster, context.user))
+            .unwrap_or_default(),
+        EntryKind::Cluster => {
+            let server = kubeconfig
+                .clusters
+                .iter()
+                .find(|cluster| cluster.name == name)
+                .and_then(|cluster| cluster.cluster.as_ref())
+                .and_then(|cluster| cluster.server.clone())
+                .unwrap_or_else(|| String::from("unknown server"));
+            let used = contexts()
+                .filter(|(_, context)| context.cluster == name)
+                .map(|(context_name, _)| context_name)
+                .collect();
+            format!("{}  ·  {}", server, used_by(used))
+        }
+        EntryKind::User => used_by(
+            contexts()
+                .filter(|(_, context)| context.user == name)
+                .map(|(context_name, _)| context_name)
+                .collect(),
+        ),
+    }
+}
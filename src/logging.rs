@@ -0,0 +1,114 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use tracing_subscriber::{
+    filter::{LevelFilter, Targets},
+    fmt::{format::FmtSpan, MakeWriter},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+const LOG_DIR: &str = "iced-k8s";
+const LOG_FILE: &str = "iced-k8s.log";
+
+/// Number of log lines that are kept around to be shown in the debug log
+const RECENT_LINES: usize = 500;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Sets up logging to stderr, to `log_file`, and to the buffer that's shown in the debug log.
+///
+/// Only messages from this app are logged at `level`; the libraries it uses only log warnings and errors.
+pub fn init(level: LevelFilter, log_file: Option<PathBuf>) {
+    let filter = Targets::new()
+        .with_default(level.min(LevelFilter::WARN))
+        .with_target(env!("CARGO_CRATE_NAME"), level);
+
+    let file_layer = log_file
+        .or_else(default_log_file)
+        .and_then(|path| match open_log_file(&path) {
+            Ok(file) => Some(file),
+            Err(error) => {
+                eprintln!("Unable to write the log to {}: {}", path.display(), error);
+                None
+            }
+        })
+        .map(|file| {
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_span_events(FmtSpan::CLOSE)
+                .with_writer(Mutex::new(file))
+        });
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_span_events(FmtSpan::CLOSE)
+                .with_writer(io::stderr)
+                .and_then(file_layer)
+                .and_then(
+                    tracing_subscriber::fmt::layer()
+                        .with_ansi(false)
+                        .with_span_events(FmtSpan::CLOSE)
+                        .with_writer(RecentLines),
+                )
+                .with_filter(filter),
+        )
+        .init();
+}
+
+/// # Returns
+/// The most recent lines that were logged, oldest first
+pub fn recent_lines() -> Vec<String> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn default_log_file() -> Option<PathBuf> {
+    dirs::cache_dir().map(|cache_dir| cache_dir.join(LOG_DIR).join(LOG_FILE))
+}
+
+fn open_log_file(path: &PathBuf) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    File::options().create(true).append(true).open(path)
+}
+
+/// Writes each formatted log line into the ring buffer of recent lines
+struct RecentLines;
+
+impl Write for RecentLines {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Ok(mut recent) = RECENT.lock() {
+            let text = String::from_utf8_lossy(buf);
+            for line in text.lines().filter(|line| !line.is_empty()) {
+                if recent.len() == RECENT_LINES {
+                    recent.pop_front();
+                }
+                recent.push_back(line.to_owned());
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for RecentLines {
+    type Writer = RecentLines;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentLines
+    }
+}
@@ -1,66 +1,166 @@
 use crate::error::Error;
+use audit::AuditEntry;
+use clap::Parser;
+use cli::Cli;
 use cluster::Cluster;
+use command_palette::{CommandPalette, PaletteCommand};
+use connection::{Connection, ConnectionId, Health};
 use container_theme::{as_container_theme, ContainerTheme};
 use context_selector::ContextSelector;
 use custom_widgets::toast::{self, Toast};
-use iced::widget::{column, container, text};
+use history::History;
+use iced::clipboard;
+use iced::keyboard;
+use iced::widget::{column, container, row, text, text_input};
 use iced::Command;
 use iced::Length;
 use iced::Settings;
 use iced::Theme;
-use iced::{Application, Element};
-use messages::{ClusterMessage, Message};
-use std::time;
+use iced::{window, Application, Element, Event};
+use kube_context::Safety;
+use kubeconfig_editor::KubeconfigEditor;
+use messages::{ClusterMessage, ContextSelectorMessage, Message};
+use notifications::NotificationCentre;
+use settings::SettingsEditor;
+use shortcuts::Shortcut;
+use state::{AppState, WindowGeometry};
+use std::{path::PathBuf, time};
+use tab::{Tab, TabId, TabRequest};
+use undo_confirmation::UndoConfirmation;
 
+mod audit;
+mod batch;
 mod button_theme;
+mod circular_loading_theme;
+mod cli;
 mod cluster;
 mod cluster_object;
 mod colours;
+mod command_palette;
+mod connection;
 mod constants;
 mod container_theme;
 mod context_selector;
 mod custom_widgets;
+mod debug_log;
 mod error;
+mod history;
 mod kube_context;
 mod kube_interface;
+mod kubeconfig;
+mod kubeconfig_editor;
+mod logging;
 mod messages;
+mod metadata;
+mod metadata_editor;
+mod notifications;
+mod permission_explorer;
+mod permissions;
+mod pod_logs;
 mod resource_type;
+mod settings;
+mod shortcuts;
+mod sidebar;
 mod sizes;
+mod state;
+mod tab;
+mod undo_confirmation;
 mod utils;
 mod workloads;
-mod circular_loading_theme;
+mod yaml_editor;
 
 /// Based on the pokedex entry from the iced repo
 pub fn main() -> iced::Result {
-    WorkloadExplorer::run(Settings::default())
+    let cli = Cli::parse();
+    logging::init(cli.log_level, cli.log_file.clone());
+
+    WorkloadExplorer::run(Settings {
+        // the state is saved before closing the window
+        exit_on_close_request: false,
+        ..Settings::with_flags(cli)
+    })
 }
 
 #[derive(Debug)]
 struct WorkloadExplorer {
-    cluster: Option<Cluster>,
+    connections: Vec<Connection>,
+    active_connection: Option<ConnectionId>,
+    /// Used to hand out ids to connections and tabs
+    next_id: usize,
+    /// Number of times the refresh timer has fired
+    refresh_ticks: u64,
     error: Option<Error>,
     context_selector: Option<ContextSelector>,
+    settings_editor: Option<SettingsEditor>,
+    kubeconfig_editor: Option<KubeconfigEditor>,
+    history: Option<History>,
     toasts: Vec<Toast>,
+    notification_centre: NotificationCentre,
+    /// Whether the notification centre is being shown
+    notifications_open: bool,
+    /// Whether the debug log is being shown
+    debug_log_open: bool,
+    /// The modifier keys that are being held
+    modifiers: keyboard::Modifiers,
+    command_palette: Option<CommandPalette>,
+    /// An undo in a protected context, until the name of the object is typed
+    undo_confirmation: Option<UndoConfirmation>,
+    /// What was asked for on the command line, until the first context is loaded
+    startup: Option<Cli>,
+    /// Read instead of `$KUBECONFIG` or `~/.kube/config`, from `--kubeconfig`
+    kubeconfig: Option<PathBuf>,
+    state: AppState,
 }
 
 impl Application for WorkloadExplorer {
     type Executor = iced::executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = Cli;
+
+    fn new(flags: Cli) -> (Self, iced::Command<Self::Message>) {
+        let state = state::load();
+        let kubeconfig_files =
+            kubeconfig::kubeconfig_paths(flags.kubeconfig.as_deref(), &state.extra_kubeconfigs);
+
+        let mut commands = vec![match flags.context.clone().or(state.last_context.clone()) {
+            Some(last_context) => Command::perform(
+                kube_interface::load_named_context(last_context, kubeconfig_files),
+                Message::ContextLoaded,
+            ),
+            None => Command::perform(
+                kube_interface::fetch_current_context(kubeconfig_files),
+                Message::ContextLoaded,
+            ),
+        }];
+        if let Some(window) = state.window {
+            commands.push(window::resize(iced::Size::new(window.width, window.height)));
+            commands.push(window::move_to(window.x, window.y));
+        }
 
-    fn new(_flags: ()) -> (Self, iced::Command<Self::Message>) {
         (
             WorkloadExplorer {
-                cluster: None,
+                connections: vec![],
+                active_connection: None,
+                next_id: 0,
+                refresh_ticks: 0,
                 error: None,
                 context_selector: None,
+                settings_editor: None,
+                kubeconfig_editor: None,
+                history: None,
+                notification_centre: NotificationCentre::default(),
+                notifications_open: false,
+                debug_log_open: false,
+                modifiers: keyboard::Modifiers::default(),
+                command_palette: None,
+                undo_confirmation: None,
                 toasts: vec![],
+                kubeconfig: flags.kubeconfig.clone(),
+                startup: Some(flags),
+                state,
             },
-            Command::perform(
-                kube_interface::fetch_current_context(),
-                Message::ContextLoaded,
-            ),
+            Command::batch(commands),
         )
     }
 
@@ -68,30 +168,464 @@ impl Application for WorkloadExplorer {
         "Workload Explorer".to_string()
     }
 
+    fn theme(&self) -> Theme {
+        self.state.settings.theme.palette().to_theme()
+    }
+
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::ContextLoaded(Ok(context)) => {
                 self.context_selector = None;
-                self.cluster = Some(Cluster::new(context.clone(), None));
+
+                // switch to the context if it's already connected instead of connecting twice
+                if let Some(connection) = self
+                    .connections
+                    .iter()
+                    .find(|connection| connection.get_context().get_name() == context.get_name())
+                {
+                    self.active_connection = Some(connection.get_id());
+                    self.save_state();
+                    return Command::none();
+                }
+
+                // a namespace given on the command line wins over the one the user picked last time
+                let startup = self.startup.take();
+                let namespace = startup
+                    .as_ref()
+                    .and_then(|startup| startup.namespace.clone())
+                    .or_else(|| self.state.namespaces.get(&context.get_name()).cloned());
+                let context = match namespace {
+                    Some(namespace) => context.with_namespace(namespace),
+                    None => context,
+                };
+                if let Some(impersonation) = self.state.impersonations.get(&context.get_name()) {
+                    context.set_impersonation(Some(impersonation.clone()));
+                }
+                context.set_read_only(self.state.is_read_only(&context.get_name()));
+                context.set_protected(
+                    self.state
+                        .settings
+                        .is_protected_context(&context.get_name()),
+                );
+
+                let id = self.next_id();
+                let connection = Connection::new(id, context.clone());
+                let mut commands = vec![connection.check_health()];
+                self.connections.push(connection);
+                self.active_connection = Some(id);
+
+                let saved_tabs = self
+                    .state
+                    .open_tabs
+                    .get(&context.get_name())
+                    .cloned()
+                    .unwrap_or_default();
+                if let Some(startup) = startup.filter(Cli::has_startup_view) {
+                    let context = if startup.all_namespaces {
+                        context.with_all_namespaces()
+                    } else {
+                        context
+                    };
+                    commands.push(self.open_tab(TabRequest::Namespace(context, startup.resource)));
+                } else if saved_tabs.is_empty() {
+                    commands.push(self.open_tab(TabRequest::Namespace(context, None)));
+                } else {
+                    for saved_tab in saved_tabs {
+                        commands.push(self.open_tab(TabRequest::from_saved(&context, saved_tab)));
+                    }
+                }
+                self.save_state();
+
+                Command::batch(commands)
+            }
+            Message::ClusterMessage(id, mut message) => {
+                // the checkboxes can't tell whether shift was held, so it's filled in here
+                if let ClusterMessage::SelectionToggled { range, .. } = &mut message {
+                    *range = self.modifiers.shift();
+                }
+                match self.find_tab_mut(id) {
+                    Some(Tab::Cluster(cluster)) => cluster.update(message),
+                    // the tab was closed while the message was in flight
+                    _ => Command::none(),
+                }
+            }
+            Message::PodLogsMessage(id, message) => match self.find_tab_mut(id) {
+                Some(Tab::PodLogs(pod_logs)) => pod_logs.update(message),
+                _ => Command::none(),
+            },
+            Message::YamlEditorMessage(id, message) => match self.find_tab_mut(id) {
+                Some(Tab::YamlEditor(yaml_editor)) => yaml_editor.update(message),
+                _ => Command::none(),
+            },
+            Message::PermissionExplorerMessage(id, message) => match self.find_tab_mut(id) {
+                Some(Tab::PermissionExplorer(permission_explorer)) => {
+                    permission_explorer.update(message)
+                }
+                _ => Command::none(),
+            },
+            Message::ConnectionSelected(id) => {
+                self.active_connection = Some(id);
+
+                self.save_state();
+
+                Command::none()
+            }
+            Message::DisconnectRequested(id) => {
+                self.connections
+                    .retain(|connection| connection.get_id() != id);
+                if self.active_connection == Some(id) {
+                    self.active_connection = self.connections.first().map(Connection::get_id);
+                }
+                self.save_state();
+
+                if self.connections.is_empty() {
+                    Command::perform(utils::resolved(), |_ignored| {
+                        Message::ChangeContextRequested
+                    })
+                } else {
+                    Command::none()
+                }
+            }
+            Message::HealthChecked(id, Err(error)) if error.is_auth_expired() => {
+                match self
+                    .connections
+                    .iter()
+                    .find(|connection| connection.get_id() == id)
+                {
+                    Some(connection) => {
+                        let context_name = connection.get_context().get_name();
+                        self.update(Message::AuthExpired(context_name))
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::HealthChecked(id, res) => {
+                if let Some(connection) = self
+                    .connections
+                    .iter_mut()
+                    .find(|connection| connection.get_id() == id)
+                {
+                    connection.set_health(match res {
+                        Ok(..) => Health::Reachable,
+                        Err(..) => Health::Unreachable,
+                    });
+                }
+
+                Command::none()
+            }
+            Message::AuthExpired(context_name) => {
+                if let Some(connection) = self
+                    .connections
+                    .iter_mut()
+                    .find(|connection| connection.get_context().get_name() == context_name)
+                {
+                    // every tab fails at once, but the user should only be asked once
+                    if !connection.is_auth_expired() {
+                        tracing::warn!(context = %context_name, "credentials expired, pausing refreshes");
+                        connection.set_auth_expired(true);
+                    }
+                }
+
+                Command::none()
+            }
+            Message::ReauthenticateRequested(id) => {
+                match self
+                    .connections
+                    .iter()
+                    .find(|connection| connection.get_id() == id)
+                {
+                    Some(connection) => connection.reauthenticate(self.kubeconfig_files()),
+                    None => Command::none(),
+                }
+            }
+            Message::Reauthenticated(id, Ok(config)) => {
+                let Some(connection) = self
+                    .connections
+                    .iter_mut()
+                    .find(|connection| connection.get_id() == id)
+                else {
+                    return Command::none();
+                };
+                tracing::info!(context = %connection.get_context().get_name(), "re-authenticated");
+                // every tab shares the context's config, so they all pick up the new credentials
+                connection.get_context().set_config(config);
+                connection.set_auth_expired(false);
+                let toast = Toast::new(
+                    toast::Status::Success,
+                    "Re-authenticated",
+                    format!("Logged in to {} again", connection.get_context().get_name()),
+                );
+
+                Command::batch(vec![
+                    connection.reload(true, true),
+                    connection.check_health(),
+                    Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast)),
+                ])
+            }
+            Message::Reauthenticated(_, Err(error)) => {
+                tracing::warn!(%error, "unable to re-authenticate");
+                let toast = error.to_toast("Unable to re-authenticate.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            Message::ImpersonationMessage(id, message) => {
+                let Some(connection) = self
+                    .connections
+                    .iter_mut()
+                    .find(|connection| connection.get_id() == id)
+                else {
+                    return Command::none();
+                };
+                if !connection.update_impersonation(message) {
+                    return Command::none();
+                }
+                let context_name = connection.get_context().get_name();
+                let toast = match connection.get_context().get_impersonation() {
+                    Some(impersonation) => {
+                        tracing::info!(context = %context_name, %impersonation, "impersonating");
+                        self.state
+                            .impersonations
+                            .insert(context_name.clone(), impersonation.clone());
+                        Toast::new(
+                            toast::Status::Primary,
+                            "Impersonating",
+                            format!("Requests to {} are sent as {}", context_name, impersonation),
+                        )
+                    }
+                    None => {
+                        tracing::info!(context = %context_name, "stopped impersonating");
+                        self.state.impersonations.remove(&context_name);
+                        Toast::new(
+                            toast::Status::Primary,
+                            "Stopped impersonating",
+                            format!("Requests to {} are sent as yourself again", context_name),
+                        )
+                    }
+                };
+                let reload = connection.reload(true, true);
+                self.save_state();
+
+                Command::batch(vec![
+                    reload,
+                    Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast)),
+                ])
+            }
+            Message::ReadOnlyToggled(id) => {
+                if let Some(connection) = self
+                    .connections
+                    .iter()
+                    .find(|connection| connection.get_id() == id)
+                {
+                    let context = connection.get_context();
+                    let read_only = !context.is_read_only();
+                    context.set_read_only(read_only);
+                    self.state.set_read_only(context.get_name(), read_only);
+                    self.save_state();
+                }
+
+                Command::none()
+            }
+            Message::NewTabRequested(id) => {
+                match self
+                    .connections
+                    .iter()
+                    .find(|connection| connection.get_id() == id)
+                {
+                    Some(connection) => {
+                        let context = connection.get_context().clone();
+                        let command = self.open_tab(TabRequest::Namespace(context, None));
+                        self.save_state();
+
+                        command
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::OpenTab(request) => {
+                let command = self.open_tab(request);
+                self.save_state();
+
+                command
+            }
+            Message::TabSelected(id) => match self.find_connection_with_tab_mut(id) {
+                Some(connection) => connection.select_tab(id),
+                None => Command::none(),
+            },
+            Message::TabClosed(id) => {
+                if let Some(connection) = self.find_connection_with_tab_mut(id) {
+                    connection.close_tab(id);
+                }
+
+                self.save_state();
 
                 Command::none()
             }
-            Message::ClusterMessage(message) => match self.cluster {
-                Some(..) => self
-                    .cluster
-                    .as_mut()
-                    .expect("content from cluster received, so should be connect to cluster")
-                    .update(message),
+            Message::TabMoved(id, offset) => {
+                if let Some(connection) = self.find_connection_with_tab_mut(id) {
+                    connection.move_tab(id, offset);
+                }
+
+                self.save_state();
+
+                Command::none()
+            }
+            Message::NamespaceChanged(context_name, namespace) => {
+                self.state.namespaces.insert(context_name, namespace);
+
+                self.save_state();
+
+                Command::none()
+            }
+            Message::FavouriteNamespaceToggled(context_name, namespace) => {
+                self.state
+                    .toggle_favourite_namespace(context_name, namespace);
+
+                self.save_state();
+
+                Command::none()
+            }
+            Message::WindowResized(width, height) => {
+                // minimizing the window resizes it to nothing on some platforms
+                if width == 0 || height == 0 {
+                    return Command::none();
+                }
+                // only saved when the window is closed, since resizing produces a lot of events
+                let window = self.window_geometry();
+                self.state.window = Some(WindowGeometry {
+                    width,
+                    height,
+                    ..window
+                });
+
+                Command::none()
+            }
+            Message::WindowMoved(x, y) => {
+                let window = self.window_geometry();
+                self.state.window = Some(WindowGeometry { x, y, ..window });
+
+                Command::none()
+            }
+            Message::SettingsRequested => {
+                self.settings_editor = Some(SettingsEditor::new(self.state.settings.clone()));
+
+                Command::none()
+            }
+            Message::SettingsEditorMessage(message) => match self.settings_editor.as_mut() {
+                Some(settings_editor) => settings_editor.update(message),
                 None => Command::none(),
             },
+            Message::SettingsSaved(settings) => {
+                // the protected patterns may have changed
+                for connection in &self.connections {
+                    let context = connection.get_context();
+                    context.set_protected(settings.is_protected_context(&context.get_name()));
+                }
+                self.state.settings = settings;
+                self.settings_editor = None;
+                self.save_state();
+
+                Command::perform(utils::resolved(), |_ignored| {
+                    Message::AddToast(Toast::new(
+                        toast::Status::Primary,
+                        "Settings saved",
+                        "Your settings will be used from now on",
+                    ))
+                })
+            }
+            Message::SettingsClosed => {
+                self.settings_editor = None;
+
+                Command::none()
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+
+                Command::none()
+            }
+            Message::ShortcutPressed(Shortcut::Escape) if self.undo_confirmation.is_some() => {
+                self.undo_confirmation = None;
+
+                Command::none()
+            }
+            Message::ShortcutPressed(shortcut) => {
+                // shortcuts are only for the tabs, not for the screens that cover them
+                if !self.is_showing_connection() {
+                    return Command::none();
+                }
+                match shortcut {
+                    Shortcut::CommandPalette => {
+                        self.command_palette = Some(CommandPalette::default());
+
+                        text_input::focus(CommandPalette::id())
+                    }
+                    Shortcut::Escape if self.command_palette.is_some() => {
+                        self.command_palette = None;
+
+                        Command::none()
+                    }
+                    shortcut => match self.active_cluster_mut() {
+                        Some(cluster) => cluster.update(ClusterMessage::ShortcutPressed(shortcut)),
+                        None => Command::none(),
+                    },
+                }
+            }
+            Message::CommandPaletteChanged(value) => {
+                if let Some(command_palette) = self.command_palette.as_mut() {
+                    command_palette.set_field_value(value);
+                }
+
+                Command::none()
+            }
+            Message::CommandPaletteSubmitted => {
+                let Some(command_palette) = self.command_palette.as_mut() else {
+                    return Command::none();
+                };
+                match command_palette.parse() {
+                    Ok(command) => {
+                        self.command_palette = None;
+
+                        self.run_palette_command(command)
+                    }
+                    Err(error) => {
+                        command_palette.set_error(error);
+
+                        Command::none()
+                    }
+                }
+            }
+            Message::CloseRequested => {
+                self.save_state();
+
+                window::close()
+            }
+            Message::RefreshTick => {
+                self.refresh_ticks += 1;
+                let include_background = self
+                    .refresh_ticks
+                    .is_multiple_of(constants::BACKGROUND_REFRESH_TICKS);
+                tracing::debug!(
+                    tick = self.refresh_ticks,
+                    include_background,
+                    "refreshing tabs"
+                );
+                let active_connection = self.active_connection;
+
+                Command::batch(self.connections.iter_mut().map(|connection| {
+                    let is_active = Some(connection.get_id()) == active_connection;
+                    connection.reload(is_active, include_background)
+                }))
+            }
+            Message::HealthCheckTick => {
+                Command::batch(self.connections.iter().map(Connection::check_health))
+            }
             Message::ContextLoaded(Err(error)) => {
-                println!("{}", error.get_message());
+                // the startup view was meant for the context that couldn't be loaded
+                self.startup = None;
+                tracing::error!("Unable to load the context: {}", error.get_message());
+                let toast = error.to_toast("Unable to load given context.");
                 Command::batch(vec![
-                    Command::perform(utils::resolved(), |_ignored: ()| {
-                        Message::AddToast(
-                            "Unable to load given context. Please select a different context."
-                                .into(),
-                        )
+                    Command::perform(utils::resolved(), move |_ignored: ()| {
+                        Message::AddToast(toast)
                     }),
                     Command::perform(utils::resolved(), |_ignored| {
                         Message::ChangeContextRequested
@@ -99,36 +633,348 @@ impl Application for WorkloadExplorer {
                 ])
             }
             Message::ChangeContextRequested => {
-                self.cluster = None;
                 self.error = None;
 
                 Command::perform(
-                    kube_interface::get_all_contexts(),
+                    kube_interface::get_all_contexts(self.kubeconfig_files()),
                     Message::AllContextsLoaded,
                 )
             }
-            Message::AllContextsLoaded(Ok(all_contexts)) => {
-                self.context_selector = Some(ContextSelector::new(all_contexts));
+            Message::ChangeContextCancelled => {
+                self.context_selector = None;
 
                 Command::none()
             }
+            Message::AllContextsLoaded(Ok(merged)) => {
+                let context_selector = ContextSelector::new(
+                    merged,
+                    !self.connections.is_empty(),
+                    self.kubeconfig_files(),
+                    self.state.extra_kubeconfigs.clone(),
+                );
+                let command = context_selector.probe_all();
+                self.context_selector = Some(context_selector);
+
+                command
+            }
+            Message::KubeconfigAdded(Ok(path)) => {
+                if !self.state.extra_kubeconfigs.contains(&path) {
+                    self.state.extra_kubeconfigs.push(path);
+                    self.save_state();
+                }
+
+                self.update(Message::ChangeContextRequested)
+            }
+            Message::KubeconfigAdded(Err(error)) => {
+                let toast = error.to_toast("Unable to add the kubeconfig file.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            Message::KubeconfigRemoved(path) => {
+                self.state
+                    .extra_kubeconfigs
+                    .retain(|extra_kubeconfig| *extra_kubeconfig != path);
+                self.save_state();
+
+                self.update(Message::ChangeContextRequested)
+            }
+            Message::KubeconfigContextRequested(name) => Command::perform(
+                kubeconfig::use_context(name.clone(), self.kubeconfig_files()),
+                move |res| Message::KubeconfigContextWritten(name, res),
+            ),
+            Message::KubeconfigContextWritten(name, Ok(path)) => {
+                tracing::info!(context = %name, path = %path.display(), "set current-context");
+                if let Some(context_selector) = self.context_selector.as_mut() {
+                    context_selector.set_current(&name);
+                }
+                let toast = Toast::new(
+                    toast::Status::Success,
+                    "Kubeconfig updated",
+                    format!(
+                        "{} is now the current context in {}. Comments in the file weren't kept, but a backup was made next to it",
+                        name,
+                        path.display()
+                    ),
+                );
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            Message::KubeconfigContextWritten(_, Err(error))
+            | Message::KubeconfigNamespaceWritten(_, _, Err(error)) => {
+                tracing::error!(%error, "unable to update the kubeconfig");
+                let toast = error.to_toast("Unable to update the kubeconfig.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            Message::KubeconfigNamespaceRequested(context_name, namespace) => Command::perform(
+                kubeconfig::set_context_namespace(
+                    context_name.clone(),
+                    namespace.clone(),
+                    self.kubeconfig_files(),
+                ),
+                move |res| Message::KubeconfigNamespaceWritten(context_name, namespace, res),
+            ),
+            Message::KubeconfigNamespaceWritten(context_name, namespace, Ok(path)) => {
+                tracing::info!(
+                    context = %context_name,
+                    %namespace,
+                    path = %path.display(),
+                    "set default namespace"
+                );
+                let toast = Toast::new(
+                    toast::Status::Success,
+                    "Kubeconfig updated",
+                    format!(
+                        "{} is now the default namespace of {} in {}. Comments in the file weren't kept, but a backup was made next to it",
+                        namespace,
+                        context_name,
+                        path.display()
+                    ),
+                );
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
             Message::AllContextsLoaded(Err(error)) => {
                 self.error = Some(error);
 
                 Command::none()
             }
             Message::ContextSelectorMessage(message) => {
-                self.context_selector.as_mut().unwrap().update(message)
+                if let ContextSelectorMessage::Probed(name, res) = &message {
+                    // remembered so that contexts that have been unreachable for a long time can be cleaned up
+                    if self.state.record_probe(name, res.is_ok()) {
+                        self.save_state();
+                    }
+                }
+                match self.context_selector.as_mut() {
+                    Some(context_selector) => context_selector.update(message),
+                    None => Command::none(),
+                }
             }
-            Message::AddToast(message) => {
-                self.toasts.push(Toast {
-                    title: "Error".into(),
-                    body: message,
-                    status: toast::Status::Danger,
-                });
+            Message::KubeconfigEditorRequested => Command::perform(
+                kubeconfig::read_files(self.kubeconfig_files()),
+                Message::KubeconfigFilesLoaded,
+            ),
+            Message::KubeconfigFilesLoaded(Ok(files)) => {
+                self.kubeconfig_editor = Some(KubeconfigEditor::new(
+                    files,
+                    self.state.unreachable_since.clone(),
+                ));
+
+                Command::none()
+            }
+            Message::KubeconfigFilesLoaded(Err(error)) => {
+                let toast = error.to_toast("Unable to read the kubeconfig files.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            Message::KubeconfigEditorMessage(message) => match self.kubeconfig_editor.as_mut() {
+                Some(kubeconfig_editor) => kubeconfig_editor.update(message),
+                None => Command::none(),
+            },
+            Message::ContextRenamed(from, to) => {
+                // the tabs that are open are remembered under the new name,
+                // and come back when the context is connected to again by that name
+                self.sync_state();
+                self.state.rename_context(&from, &to);
+                self.connections
+                    .retain(|connection| connection.get_context().get_name() != from);
+                if !self
+                    .connections
+                    .iter()
+                    .any(|connection| Some(connection.get_id()) == self.active_connection)
+                {
+                    self.active_connection = self.connections.first().map(Connection::get_id);
+                }
+                // saved without syncing, so that the renamed context is still the last one if it was
+                if let Err(error) = state::save(&self.state) {
+                    tracing::error!("Unable to save state: {}", error.get_message());
+                }
+
+                Command::none()
+            }
+            Message::KubeconfigEditorClosed => {
+                self.kubeconfig_editor = None;
+
+                // the contexts may have been renamed or removed
+                self.update(Message::ChangeContextRequested)
+            }
+            Message::AddToast(toast) => {
+                self.notification_centre.push(toast.clone());
+                if self.notifications_open {
+                    self.notification_centre.mark_read();
+                }
+                self.toasts.push(toast);
+
+                Command::none()
+            }
+            Message::ToggleToastDetails(index) => {
+                if let Some(toast) = self.toasts.get_mut(index) {
+                    toast.expanded = !toast.expanded;
+                }
+
+                Command::none()
+            }
+            Message::CopyToastDetails(index) => {
+                match self
+                    .toasts
+                    .get(index)
+                    .and_then(|toast| toast.details.clone())
+                {
+                    Some(details) => clipboard::write(details),
+                    None => Command::none(),
+                }
+            }
+            Message::DebugLogRequested => {
+                self.debug_log_open = true;
+
+                Command::none()
+            }
+            Message::DebugLogClosed => {
+                self.debug_log_open = false;
+
+                Command::none()
+            }
+            Message::DebugLogCopyRequested => clipboard::write(logging::recent_lines().join("\n")),
+            Message::NotificationsRequested => {
+                self.notifications_open = true;
+                self.notification_centre.mark_read();
+
+                Command::none()
+            }
+            Message::NotificationsClosed => {
+                self.notifications_open = false;
+
+                Command::none()
+            }
+            Message::NotificationCentreMessage(message) => self.notification_centre.update(message),
+            Message::ActionPerformed(entry) => {
+                if let Err(error) = audit::record(&entry) {
+                    tracing::warn!(
+                        "Unable to record {} in the audit trail: {}",
+                        entry.action,
+                        error
+                    );
+                }
+                if let Some(history) = self.history.as_mut() {
+                    history.push(entry);
+                }
+
+                Command::none()
+            }
+            Message::HistoryRequested => Command::perform(audit::read(), Message::HistoryLoaded),
+            Message::HistoryLoaded(Ok(entries)) => {
+                self.history = Some(History::new(entries));
+
+                Command::none()
+            }
+            Message::HistoryLoaded(Err(error)) => {
+                tracing::warn!("Unable to read the audit trail: {}", error);
+                let toast = error.to_toast("Unable to read the history.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            Message::HistoryClosed => {
+                self.history = None;
+
+                Command::none()
+            }
+            Message::ToastActionRequested(index) => {
+                if index >= self.toasts.len() {
+                    return Command::none();
+                }
+                // the action can only be taken once
+                match self.toasts.remove(index).action {
+                    Some(action) => self.update(*action.message),
+                    None => Command::none(),
+                }
+            }
+            Message::RecreateRequested(context, cluster_object, snapshot) => {
+                if context.is_read_only() {
+                    let toast = Toast::new(
+                        toast::Status::Danger,
+                        "Read-only",
+                        format!(
+                            "{} is read-only, so nothing can be created in it",
+                            context.get_name()
+                        ),
+                    );
+                    return Command::perform(utils::resolved(), move |_ignored| {
+                        Message::AddToast(toast)
+                    });
+                }
+                if context.get_safety() == Safety::Protected {
+                    self.undo_confirmation =
+                        Some(UndoConfirmation::new(context, cluster_object, snapshot));
+                    return Command::none();
+                }
+                Command::perform(
+                    kube_interface::recreate(context.clone(), cluster_object.clone(), snapshot),
+                    move |res| Message::Recreated(context, cluster_object, res),
+                )
+            }
+            Message::RecreateConfirmationFieldChanged(value) => {
+                if let Some(undo_confirmation) = self.undo_confirmation.as_mut() {
+                    undo_confirmation.set_field_value(value);
+                }
 
                 Command::none()
             }
+            Message::RecreateConfirmed => {
+                // the name has to match before anything is created
+                if !self
+                    .undo_confirmation
+                    .as_ref()
+                    .is_some_and(UndoConfirmation::is_confirmed)
+                {
+                    return Command::none();
+                }
+                let Some((context, cluster_object, snapshot)) = self
+                    .undo_confirmation
+                    .take()
+                    .map(UndoConfirmation::into_parts)
+                else {
+                    return Command::none();
+                };
+                Command::perform(
+                    kube_interface::recreate(context.clone(), cluster_object.clone(), snapshot),
+                    move |res| Message::Recreated(context, cluster_object, res),
+                )
+            }
+            Message::RecreateCancelled => {
+                self.undo_confirmation = None;
+
+                Command::none()
+            }
+            Message::Recreated(context, cluster_object, res) => {
+                let entry = AuditEntry::new(
+                    &context,
+                    cluster_object.namespace.clone(),
+                    audit::Action::Recreate,
+                    cluster_object.r#type,
+                    cluster_object.name.clone(),
+                    res.as_ref().copied(),
+                );
+                let toast = match res {
+                    Ok(()) => Toast::new(
+                        toast::Status::Success,
+                        "Restored",
+                        format!(
+                            "Created {} {} again",
+                            cluster_object.r#type, cluster_object.name
+                        ),
+                    ),
+                    Err(error) => {
+                        tracing::warn!("Unable to recreate resource: {}", error.get_message());
+                        error.to_toast("Unable to undo the delete.")
+                    }
+                };
+
+                Command::batch(vec![
+                    self.update(Message::ActionPerformed(entry)),
+                    Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast)),
+                ])
+            }
             Message::CloseToast(index) => {
                 self.toasts.remove(index);
 
@@ -137,12 +983,20 @@ impl Application for WorkloadExplorer {
         }
     }
 
-    fn view(&self) -> Element<Message> {
-        let content = if self.error.is_some() {
+    fn view(&self) -> Element<'_, Message> {
+        let palette = self.state.settings.theme.palette();
+        let active_connection = self
+            .connections
+            .iter()
+            .find(|connection| Some(connection.get_id()) == self.active_connection);
+
+        let content = if let Some(error) = self.error.as_ref() {
             container(
-                column![text(self.error.as_ref().unwrap().get_message())
-                    .size(40)
-                    .style(colours::get_red()),]
+                column![
+                    text(error.get_remediation()).size(40).style(palette.danger),
+                    text(error.get_message()).style(palette.secondary_text),
+                ]
+                .spacing(sizes::SEP)
                 .width(Length::Shrink),
             )
             .style(as_container_theme(ContainerTheme::Light))
@@ -151,10 +1005,29 @@ impl Application for WorkloadExplorer {
             .center_x()
             .center_y()
             .into()
-        } else if self.cluster.is_some() {
-            self.cluster.as_ref().unwrap().view()
-        } else if self.context_selector.is_some() {
-            self.context_selector.as_ref().unwrap().view()
+        } else if let Some(settings_editor) = self.settings_editor.as_ref() {
+            settings_editor.view(palette)
+        } else if self.debug_log_open {
+            debug_log::view(palette)
+        } else if self.notifications_open {
+            self.notification_centre.view(palette)
+        } else if let Some(history) = self.history.as_ref() {
+            history.view(palette)
+        } else if let Some(kubeconfig_editor) = self.kubeconfig_editor.as_ref() {
+            kubeconfig_editor.view(palette)
+        } else if let Some(context_selector) = self.context_selector.as_ref() {
+            context_selector.view(palette)
+        } else if let Some(connection) = active_connection {
+            let tabs = row![
+                sidebar::view(&self.connections, self.active_connection, palette),
+                connection.view(&self.state, self.notification_centre.get_unread())
+            ]
+            .width(Length::Fill)
+            .height(Length::Fill);
+            match &self.command_palette {
+                Some(command_palette) => column![command_palette.view(palette), tabs].into(),
+                None => tabs.into(),
+            }
         } else {
             container(text("loading..."))
                 .width(Length::Fill)
@@ -165,16 +1038,199 @@ impl Application for WorkloadExplorer {
                 .into()
         };
 
-        toast::Manager::new(content, &self.toasts, Message::CloseToast)
-            .timeout(constants::TOAST_TIMEOUT)
-            .into()
+        let content = match &self.undo_confirmation {
+            Some(undo_confirmation) => column![undo_confirmation.view(palette), content].into(),
+            None => content,
+        };
+
+        toast::Manager::new(
+            content,
+            &self.toasts,
+            palette,
+            Message::CloseToast,
+            Message::ToggleToastDetails,
+            Message::CopyToastDetails,
+            Message::ToastActionRequested,
+        )
+        .timeout(self.state.settings.toast_timeout)
+        .into()
     }
 
     fn subscription(&self) -> iced::Subscription<Message> {
-        match self.cluster {
-            Some(..) => iced::time::every(time::Duration::from_secs(constants::CLUSTER_REFRESH))
-                .map(|_instant| Message::ClusterMessage(ClusterMessage::ReloadRequested)),
-            None => iced::Subscription::none(),
+        iced::Subscription::batch(vec![
+            iced::subscription::events_with(|event, status| match event {
+                Event::Window(window::Event::Resized { width, height }) => {
+                    Some(Message::WindowResized(width, height))
+                }
+                Event::Window(window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+                Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+                Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                    Some(Message::ModifiersChanged(modifiers))
+                }
+                event => shortcuts::from_event(&event, status).map(Message::ShortcutPressed),
+            }),
+            iced::time::every(time::Duration::from_secs(
+                self.state.settings.refresh_interval,
+            ))
+            .map(|_instant| Message::RefreshTick),
+            iced::time::every(time::Duration::from_secs(constants::HEALTH_CHECK))
+                .map(|_instant| Message::HealthCheckTick),
+        ])
+    }
+}
+
+impl WorkloadExplorer {
+    fn next_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// # Returns
+    /// Every kubeconfig file that's read, in order of priority
+    fn kubeconfig_files(&self) -> Vec<PathBuf> {
+        kubeconfig::kubeconfig_paths(self.kubeconfig.as_deref(), &self.state.extra_kubeconfigs)
+    }
+
+    fn find_tab_mut(&mut self, id: TabId) -> Option<&mut Tab> {
+        self.connections
+            .iter_mut()
+            .find_map(|connection| connection.get_tab_mut(id))
+    }
+
+    /// Whether the tabs of a connection are being shown, rather than a screen that covers them
+    fn is_showing_connection(&self) -> bool {
+        self.error.is_none()
+            && self.settings_editor.is_none()
+            && !self.debug_log_open
+            && !self.notifications_open
+            && self.history.is_none()
+            && self.kubeconfig_editor.is_none()
+            && self.context_selector.is_none()
+            && self.active_connection.is_some()
+    }
+
+    fn active_connection_mut(&mut self) -> Option<&mut Connection> {
+        let active_connection = self.active_connection;
+        self.connections
+            .iter_mut()
+            .find(|connection| Some(connection.get_id()) == active_connection)
+    }
+
+    /// # Returns
+    /// The namespace tab that's being shown, if that's the kind of tab that's being shown
+    fn active_cluster_mut(&mut self) -> Option<&mut Cluster> {
+        let connection = self.active_connection_mut()?;
+        let active_tab = connection.get_active_tab()?;
+        match connection.get_tab_mut(active_tab) {
+            Some(Tab::Cluster(cluster)) => Some(cluster),
+            _ => None,
+        }
+    }
+
+    /// Runs a command from the command palette on the tab that's being shown.
+    /// If that isn't a namespace tab, a new one is opened instead.
+    fn run_palette_command(&mut self, command: PaletteCommand) -> Command<Message> {
+        let context = self
+            .active_connection_mut()
+            .map(|connection| connection.get_context().clone());
+        match command {
+            PaletteCommand::Context(Some(name)) => Command::perform(
+                kube_interface::load_named_context(name, self.kubeconfig_files()),
+                Message::ContextLoaded,
+            ),
+            PaletteCommand::Context(None) => Command::perform(utils::resolved(), |_ignored| {
+                Message::ChangeContextRequested
+            }),
+            PaletteCommand::Resource(resource_type) => {
+                let command = match self.active_cluster_mut() {
+                    Some(cluster) => {
+                        cluster.update(ClusterMessage::ResourceTypeSelected(resource_type))
+                    }
+                    None => match context {
+                        Some(context) => {
+                            self.open_tab(TabRequest::Namespace(context, resource_type))
+                        }
+                        None => Command::none(),
+                    },
+                };
+                self.save_state();
+
+                command
+            }
+            PaletteCommand::Namespace(namespace) => {
+                let message = match namespace {
+                    // the same as picking a favourite, which checks the namespace before switching to it
+                    Some(namespace) => ClusterMessage::FavouriteNamespaceSelected(namespace),
+                    None => ClusterMessage::ChangeNamespaceRequested,
+                };
+                if let Some(cluster) = self.active_cluster_mut() {
+                    return cluster.update(message);
+                }
+                let Some(context) = context else {
+                    return Command::none();
+                };
+                let command = self.open_tab(TabRequest::Namespace(context, None));
+                let Some(cluster) = self.active_cluster_mut() else {
+                    return command;
+                };
+                Command::batch(vec![command, cluster.update(message)])
+            }
+        }
+    }
+
+    fn find_connection_with_tab_mut(&mut self, id: TabId) -> Option<&mut Connection> {
+        self.connections
+            .iter_mut()
+            .find(|connection| connection.has_tab(id))
+    }
+
+    fn window_geometry(&self) -> WindowGeometry {
+        self.state.window.unwrap_or(WindowGeometry {
+            width: 1024,
+            height: 768,
+            x: 0,
+            y: 0,
+        })
+    }
+
+    /// Copies what the user currently has open into the state
+    fn sync_state(&mut self) {
+        self.state.last_context = self
+            .connections
+            .iter()
+            .find(|connection| Some(connection.get_id()) == self.active_connection)
+            .map(|connection| connection.get_context().get_name());
+        for connection in self.connections.iter() {
+            self.state.open_tabs.insert(
+                connection.get_context().get_name(),
+                connection.get_tabs().iter().map(Tab::to_saved).collect(),
+            );
+        }
+    }
+
+    /// Saves the state so that it can be restored the next time the app is launched
+    fn save_state(&mut self) {
+        self.sync_state();
+        if let Err(error) = state::save(&self.state) {
+            tracing::error!("Unable to save state: {}", error.get_message());
         }
     }
+
+    /// Opens a tab in the connection for the context of the request, and switches to it
+    fn open_tab(&mut self, request: TabRequest) -> Command<Message> {
+        let id = self.next_id();
+        let Some(connection) = self.connections.iter_mut().find(|connection| {
+            connection.get_context().get_name() == request.get_context().get_name()
+        }) else {
+            return Command::none();
+        };
+
+        let mut tab = Tab::new(id, request);
+        let command = tab.reload();
+        connection.add_tab(tab);
+        self.active_connection = Some(connection.get_id());
+
+        command
+    }
 }
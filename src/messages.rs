@@ -1,45 +1,285 @@
+use std::path::PathBuf;
+
+use iced::keyboard;
+use kube::{api::DynamicObject, config::Kubeconfig, Config};
+
 use crate::{
-    cluster_object::ClusterObject, error::Error, kube_context::KubeContext, workloads::Workloads,
+    audit::AuditEntry,
+    batch::{BatchAction, BatchResult},
+    cluster_object::{ClusterObject, ObjectKey},
+    connection::ConnectionId,
+    custom_widgets::toast::Toast,
+    error::Error,
+    kube_context::KubeContext,
+    kubeconfig::{self, EntryKind, KubeconfigEdit, MergedKubeconfig},
+    metadata::{Metadata, MetadataKind},
+    notifications::SeverityFilter,
+    permissions::{Access, AccessQuery, Rules},
+    resource_type::ResourceType,
+    settings::{DeletePropagation, ThemeChoice, UserSettings},
+    shortcuts::Shortcut,
+    tab::{TabId, TabRequest},
+    workloads::Workloads,
 };
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant, clippy::enum_variant_names)]
 pub enum Message {
     ContextLoaded(Result<KubeContext, Error>),
-    ClusterMessage(ClusterMessage),
+    ClusterMessage(TabId, ClusterMessage),
+    PodLogsMessage(TabId, PodLogsMessage),
+    YamlEditorMessage(TabId, YamlEditorMessage),
+    PermissionExplorerMessage(TabId, PermissionExplorerMessage),
+    ConnectionSelected(ConnectionId),
+    DisconnectRequested(ConnectionId),
+    HealthChecked(ConnectionId, Result<(), Error>),
+    /// The credentials of the named context have expired, so its connection needs to re-authenticate
+    AuthExpired(String),
+    ReauthenticateRequested(ConnectionId),
+    Reauthenticated(ConnectionId, Result<Config, Error>),
+    ImpersonationMessage(ConnectionId, ImpersonationMessage),
+    ReadOnlyToggled(ConnectionId),
+    NewTabRequested(ConnectionId),
+    OpenTab(TabRequest),
+    TabSelected(TabId),
+    TabClosed(TabId),
+    TabMoved(TabId, isize),
+    NamespaceChanged(String, String),
+    FavouriteNamespaceToggled(String, String),
+    WindowResized(u32, u32),
+    WindowMoved(i32, i32),
+    CloseRequested,
+    ModifiersChanged(keyboard::Modifiers),
+    ShortcutPressed(Shortcut),
+    CommandPaletteChanged(String),
+    CommandPaletteSubmitted,
+    SettingsRequested,
+    SettingsEditorMessage(SettingsEditorMessage),
+    SettingsSaved(UserSettings),
+    SettingsClosed,
+    RefreshTick,
+    HealthCheckTick,
     ChangeContextRequested,
-    AllContextsLoaded(Result<Vec<String>, Error>),
+    ChangeContextCancelled,
+    AllContextsLoaded(Result<MergedKubeconfig, Error>),
+    KubeconfigAdded(Result<PathBuf, Error>),
+    KubeconfigRemoved(PathBuf),
+    /// Makes the named context the `current-context` in the kubeconfig
+    KubeconfigContextRequested(String),
+    KubeconfigContextWritten(String, Result<PathBuf, Error>),
+    /// Makes the namespace the default of the named context in the kubeconfig
+    KubeconfigNamespaceRequested(String, String),
+    KubeconfigNamespaceWritten(String, String, Result<PathBuf, Error>),
     ContextSelectorMessage(ContextSelectorMessage),
+    KubeconfigEditorRequested,
+    KubeconfigFilesLoaded(Result<Vec<(PathBuf, Kubeconfig)>, Error>),
+    KubeconfigEditorMessage(KubeconfigEditorMessage),
+    KubeconfigEditorClosed,
+    /// A context was renamed in a kubeconfig file, from the first name to the second
+    ContextRenamed(String, String),
     CloseToast(usize),
-    AddToast(String),
+    AddToast(Toast),
+    ToggleToastDetails(usize),
+    CopyToastDetails(usize),
+    ToastActionRequested(usize),
+    /// Creates the object from a snapshot that was taken before it was deleted
+    RecreateRequested(KubeContext, ClusterObject, DynamicObject),
+    RecreateConfirmationFieldChanged(String),
+    /// Recreates the object once its name was typed, in a protected context
+    RecreateConfirmed,
+    RecreateCancelled,
+    Recreated(KubeContext, ClusterObject, Result<(), Error>),
+    DebugLogRequested,
+    DebugLogClosed,
+    DebugLogCopyRequested,
+    NotificationsRequested,
+    /// A change was made to a cluster, or attempted, and should be recorded in the audit trail
+    ActionPerformed(AuditEntry),
+    HistoryRequested,
+    HistoryLoaded(Result<Vec<AuditEntry>, Error>),
+    HistoryClosed,
+    NotificationsClosed,
+    NotificationCentreMessage(NotificationCentreMessage),
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum ClusterMessage {
-    WorkloadsLoaded(Result<Workloads, Error>),
+    /// Holds the number of the refresh that loaded the workloads
+    WorkloadsLoaded(u64, Result<Workloads, Error>),
     ChangeNamespaceRequested,
     NamespaceFieldChanged(String),
     NamespaceSelected(String),
-    NamespaceChecked(Result<(), Error>),
+    FavouriteNamespaceSelected(String),
+    /// Holds the namespace that was checked
+    NamespaceChecked(String, Result<(), Error>),
     ReloadRequested,
-    DeleteRequested(ClusterObject),
-    Deleted(Result<ClusterObject, Error>),
+    /// A refresh was cancelled because a newer one started
+    ReloadCancelled,
+    DeleteConfirmationRequested(ClusterObject),
+    /// The context name that was typed to confirm a delete in a protected context
+    DeleteConfirmationFieldChanged(String),
+    DeleteCancelled,
+    DeleteRequested(ClusterObject, DeletePropagation),
+    /// Holds a snapshot of the object from before it was deleted, if one could be taken
+    Deleted(ClusterObject, Result<Option<DynamicObject>, Error>),
+    LogsRequested(ClusterObject),
+    YamlRequested(ClusterObject),
+    FilterChanged(String),
+    /// `range` is set when shift was held, which selects everything from the last object that was toggled
+    SelectionToggled {
+        key: ObjectKey,
+        selected: bool,
+        range: bool,
+    },
+    SelectAllMatchingRequested,
+    SelectionCleared,
+    ReplicasFieldChanged(String),
+    LabelFieldChanged(String),
+    BatchRequested(BatchAction),
+    /// The context name that was typed to confirm a batch action in a protected context
+    BatchConfirmationFieldChanged(String),
+    BatchCancelled,
+    BatchConfirmed,
+    BatchFinished(BatchResult),
+    BatchResultDismissed,
+    MetadataEditorRequested(ClusterObject),
+    /// Edits the labels and annotations of every selected object at once
+    SelectionMetadataEditorRequested,
+    MetadataLoaded(ObjectKey, Result<Metadata, Error>),
+    MetadataEditorMessage(MetadataEditorMessage),
+    MetadataEditorCancelled,
+    MetadataApplyRequested,
+    MetadataApplied(ClusterObject, Result<(), Error>),
+    ShortcutPressed(Shortcut),
+    /// Lists only one type of resource, or every type if `None`
+    ResourceTypeSelected(Option<ResourceType>),
 }
 
-impl Into<Message> for ClusterMessage {
-    fn into(self) -> Message {
-        Message::ClusterMessage(self)
-    }
+#[derive(Debug, Clone)]
+pub enum MetadataEditorMessage {
+    KeyChanged(MetadataKind, usize, String),
+    ValueChanged(MetadataKind, usize, String),
+    /// Removes the entry, or brings it back if it was removed
+    RemoveToggled(MetadataKind, usize),
+    AddRequested(MetadataKind),
+    /// The context name that was typed to confirm the changes in a protected context
+    ConfirmationFieldChanged(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum PodLogsMessage {
+    ReloadRequested,
+    /// A refresh was cancelled because a newer one started
+    ReloadCancelled,
+    /// Holds the number of the refresh that read the logs
+    LogsLoaded(u64, Result<String, Error>),
+}
+
+#[derive(Debug, Clone)]
+pub enum YamlEditorMessage {
+    ReloadRequested,
+    RevertRequested,
+    YamlLoaded(Result<String, Error>),
+    LineChanged(usize, String),
+    NewLineRequested(usize),
+    LineRemoved(usize),
+    /// The context name that was typed to confirm applying the changes in a protected context
+    ConfirmationFieldChanged(String),
+    ApplyRequested,
+    Applied(Result<(), Error>),
+}
+
+#[derive(Debug, Clone)]
+pub enum PermissionExplorerMessage {
+    ReloadRequested,
+    RulesLoaded(Result<Rules, Error>),
+    VerbFieldChanged(String),
+    ResourceFieldChanged(String),
+    SubresourceFieldChanged(String),
+    NameFieldChanged(String),
+    CanIRequested,
+    CanIAnswered(AccessQuery, Result<Access, Error>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ImpersonationMessage {
+    EditRequested,
+    UserFieldChanged(String),
+    GroupsFieldChanged(String),
+    Cancelled,
+    ApplyRequested,
+    StopRequested,
 }
 
 #[derive(Debug, Clone)]
 pub enum ContextSelectorMessage {
-    DropDownItemSelected(String),
-    DropDownClosed,
+    FilterChanged(String),
+    EntrySelected(String),
     ContextSelected(String),
+    /// Checks a context whose credentials come from running a command, which isn't done until asked for
+    ProbeRequested(String),
+    Probed(String, Result<String, Error>),
+    KubeconfigFieldChanged(String),
+    AddKubeconfigRequested,
 }
 
 impl From<ContextSelectorMessage> for Message {
     fn from(value: ContextSelectorMessage) -> Self {
         Message::ContextSelectorMessage(value)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone)]
+pub enum SettingsEditorMessage {
+    RefreshIntervalChanged(String),
+    ToastTimeoutChanged(String),
+    ProtectedContextsChanged(String),
+    ThemeSelected(ThemeChoice),
+    PropagationPolicySelected(DeletePropagation),
+    ConfirmDeleteToggled(bool),
+    SaveRequested,
+}
+
+impl From<SettingsEditorMessage> for Message {
+    fn from(value: SettingsEditorMessage) -> Self {
+        Message::SettingsEditorMessage(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NotificationCentreMessage {
+    FilterSelected(SeverityFilter),
+    ClearRequested,
+}
+
+impl From<NotificationCentreMessage> for Message {
+    fn from(value: NotificationCentreMessage) -> Self {
+        Message::NotificationCentreMessage(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum KubeconfigEditorMessage {
+    FileSelected(PathBuf),
+    RenameRequested(EntryKind, String),
+    RenameFieldChanged(String),
+    RenameCancelled,
+    RenameConfirmed,
+    DeleteRequested(EntryKind, String),
+    ImportFieldChanged(String),
+    ImportRequested,
+    PruneRequested,
+    /// Makes the delete or prune that's waiting to be confirmed
+    EditConfirmed,
+    EditCancelled,
+    /// Holds the edit that was made, so that a renamed context can be followed
+    Edited(PathBuf, KubeconfigEdit, Result<kubeconfig::Edited, Error>),
+}
+
+impl From<KubeconfigEditorMessage> for Message {
+    fn from(value: KubeconfigEditorMessage) -> Self {
+        Message::KubeconfigEditorMessage(value)
+    }
+}
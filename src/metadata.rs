@@ -0,0 +1,189 @@
+//! Labels and annotations, and the rules the API server checks them against
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// The longest a label value, or the name part of a key, can be
+const MAX_NAME_LENGTH: usize = 63;
+/// The longest the prefix of a key can be
+const MAX_PREFIX_LENGTH: usize = 253;
+
+/// Which of the two maps of key/value pairs in the metadata of an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataKind {
+    Label,
+    Annotation,
+}
+
+/// The labels and annotations of an object
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// Changes to the labels and annotations of an object, as a JSON merge patch of its metadata.
+/// Keys that are set to `None` are removed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetadataPatch {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, Option<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, Option<String>>,
+}
+
+impl MetadataPatch {
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.annotations.is_empty()
+    }
+}
+
+/// Checks a label or annotation key, e.g. `app` or `example.com/team`
+pub fn check_key(key: &str) -> Result<(), String> {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+    if let Some(prefix) = prefix {
+        if prefix.len() > MAX_PREFIX_LENGTH || !is_dns_subdomain(prefix) {
+            return Err(String::from(
+                "The prefix must be a lowercase DNS subdomain, e.g. example.com",
+            ));
+        }
+    }
+    if name.is_empty() {
+        return Err(String::from("The key can't be empty"));
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "The name part of the key can be at most {} characters",
+            MAX_NAME_LENGTH
+        ));
+    }
+    if !is_qualified_name(name) {
+        return Err(String::from(
+            "The key can only contain letters, digits, '-', '_' and '.', and must start and end with a letter or digit",
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a label value. Annotation values can be anything.
+pub fn check_label_value(value: &str) -> Result<(), String> {
+    if value.len() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "Label values can be at most {} characters",
+            MAX_NAME_LENGTH
+        ));
+    }
+    if !value.is_empty() && !is_qualified_name(value) {
+        return Err(String::from(
+            "Label values can only contain letters, digits, '-', '_' and '.', and must start and end with a letter or digit",
+        ));
+    }
+    Ok(())
+}
+
+fn is_qualified_name(name: &str) -> bool {
+    starts_and_ends_alphanumeric(name, |character| character.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character))
+}
+
+fn is_dns_subdomain(value: &str) -> bool {
+    let is_lower_alphanumeric =
+        |character: char| character.is_ascii_lowercase() || character.is_ascii_digit();
+    value.split('.').all(|part| {
+        starts_and_ends_alphanumeric(part, is_lower_alphanumeric)
+            && part
+                .chars()
+                .all(|character| is_lower_alphanumeric(character) || character == '-')
+    })
+}
+
+fn starts_and_ends_alphanumeric(value: &str, is_alphanumeric: impl Fn(char) -> bool) -> bool {
+    match (value.chars().next(), value.chars().last()) {
+        (Some(first), Some(last)) => is_alphanumeric(first) && is_alphanumeric(last),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_keys_with_and_without_a_prefix() {
+        assert!(check_key("app").is_ok());
+        assert!(check_key("app.kubernetes.io/name").is_ok());
+        assert!(check_key("example.com/Some_Key.1").is_ok());
+    }
+
+    #[test]
+    fn checks_the_length_of_the_prefix_and_name() {
+        let name = "a".repeat(MAX_NAME_LENGTH);
+        assert!(check_key(&name).is_ok());
+        assert!(check_key(&format!("{}a", name)).is_err());
+
+        // a DNS subdomain of the longest allowed length, made of labels of at most 63 characters
+        let prefix = format!("{}.{}", "a".repeat(63), "b".repeat(MAX_PREFIX_LENGTH - 64));
+        assert!(check_key(&format!("{}/key", prefix)).is_ok());
+        assert!(check_key(&format!("{}b/key", prefix)).is_err());
+    }
+
+    #[test]
+    fn rejects_uppercase_prefixes() {
+        assert!(check_key("Example.com/key").is_err());
+        assert!(check_key("example.COM/key").is_err());
+    }
+
+    #[test]
+    fn rejects_leading_and_trailing_punctuation() {
+        for key in ["-app", "app-", ".app", "app.", "_app", "app_"] {
+            assert!(check_key(key).is_err(), "{} was accepted", key);
+        }
+        for prefix in [
+            "-example.com",
+            "example.com-",
+            "example..com",
+            ".example.com",
+        ] {
+            assert!(
+                check_key(&format!("{}/key", prefix)).is_err(),
+                "{} was accepted",
+                prefix
+            );
+        }
+        for value in ["-v1", "v1-", ".v1", "v1.", "_v1", "v1_"] {
+            assert!(check_label_value(value).is_err(), "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn rejects_empty_keys() {
+        assert!(check_key("").is_err());
+        assert!(check_key("example.com/").is_err());
+        assert!(check_key("/key").is_err());
+    }
+
+    #[test]
+    fn accepts_empty_label_values() {
+        assert!(check_label_value("").is_ok());
+    }
+
+    #[test]
+    fn checks_the_length_of_label_values() {
+        assert!(check_label_value(&"v".repeat(MAX_NAME_LENGTH)).is_ok());
+        assert!(check_label_value(&"v".repeat(MAX_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn rejects_characters_kubernetes_does_not_allow() {
+        assert!(check_key("my key").is_err());
+        assert!(check_key("a/b/c").is_err());
+        assert!(check_label_value("v=1").is_err());
+        assert!(check_label_value("café").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+
+use iced::{
+    widget::{button, column, container, horizontal_space, row, scrollable, text, text_input},
+    Alignment, Element, Length, Padding,
+};
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    cluster_object::ClusterObject,
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    kube_context::{KubeContext, Safety},
+    messages::{ClusterMessage, MetadataEditorMessage},
+    metadata::{self, Metadata, MetadataKind, MetadataPatch},
+    sizes,
+    tab::TabId,
+    Message,
+};
+
+/// What the changes are made to
+#[derive(Debug, Clone)]
+pub enum Target {
+    Object(ClusterObject),
+    /// The objects that were selected, which only get the keys that are set or removed in the editor
+    Selection(usize),
+}
+
+/// One row of the editor
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    key: String,
+    value: String,
+    /// Removed entries are kept, so that they can be brought back and, for a selection,
+    /// so that the key is removed from every object
+    removed: bool,
+}
+
+impl Entry {
+    fn is_blank(&self) -> bool {
+        self.key.trim().is_empty() && self.value.is_empty()
+    }
+}
+
+/// Adds, changes and removes the labels and annotations of an object, or of every selected object
+#[derive(Debug, Clone)]
+pub struct MetadataEditor {
+    tab_id: TabId,
+    target: Target,
+    /// What the object had when it was loaded, or nothing for a selection
+    original: Option<Metadata>,
+    labels: Vec<Entry>,
+    annotations: Vec<Entry>,
+    /// The context name that was typed to confirm the changes, in a protected context
+    confirmation_field_value: String,
+    applying: bool,
+}
+
+impl MetadataEditor {
+    /// The editor for a single object waits for its metadata to be loaded with `loaded`
+    pub fn new(tab_id: TabId, target: Target) -> MetadataEditor {
+        let original = match target {
+            Target::Object(..) => None,
+            Target::Selection(..) => Some(Metadata::default()),
+        };
+        MetadataEditor {
+            tab_id,
+            target,
+            original,
+            labels: vec![Entry::default()],
+            annotations: vec![Entry::default()],
+            confirmation_field_value: String::from(""),
+            applying: false,
+        }
+    }
+
+    pub fn get_target(&self) -> &Target {
+        &self.target
+    }
+
+    pub fn loaded(&mut self, metadata: Metadata) {
+        let entries = |map: &BTreeMap<String, String>| {
+            map.iter()
+                .map(|(key, value)| Entry {
+                    key: key.clone(),
+                    value: value.clone(),
+                    removed: false,
+                })
+                .chain(std::iter::once(Entry::default()))
+                .collect()
+        };
+        self.labels = entries(&metadata.labels);
+        self.annotations = entries(&metadata.annotations);
+        self.original = Some(metadata);
+    }
+
+    pub fn set_applying(&mut self, applying: bool) {
+        self.applying = applying;
+    }
+
+    pub fn update(&mut self, message: MetadataEditorMessage) {
+        match message {
+            MetadataEditorMessage::KeyChanged(kind, index, value) => {
+                if let Some(entry) = self.entries_mut(kind).get_mut(index) {
+                    entry.key = value;
+                }
+            }
+            MetadataEditorMessage::ValueChanged(kind, index, value) => {
+                if let Some(entry) = self.entries_mut(kind).get_mut(index) {
+                    entry.value = value;
+                }
+            }
+            MetadataEditorMessage::RemoveToggled(kind, index) => {
+                if let Some(entry) = self.entries_mut(kind).get_mut(index) {
+                    entry.removed = !entry.removed;
+                }
+            }
+            MetadataEditorMessage::AddRequested(kind) => {
+                self.entries_mut(kind).push(Entry::default());
+            }
+            MetadataEditorMessage::ConfirmationFieldChanged(value) => {
+                self.confirmation_field_value = value;
+            }
+        }
+    }
+
+    fn entries(&self, kind: MetadataKind) -> &[Entry] {
+        match kind {
+            MetadataKind::Label => &self.labels,
+            MetadataKind::Annotation => &self.annotations,
+        }
+    }
+
+    fn entries_mut(&mut self, kind: MetadataKind) -> &mut Vec<Entry> {
+        match kind {
+            MetadataKind::Label => &mut self.labels,
+            MetadataKind::Annotation => &mut self.annotations,
+        }
+    }
+
+    /// # Returns
+    /// Why the entry at `index` can't be applied, if it can't
+    fn entry_error(&self, kind: MetadataKind, index: usize) -> Option<String> {
+        let entries = self.entries(kind);
+        let entry = &entries[index];
+        if entry.is_blank() {
+            return None;
+        }
+        let key = entry.key.trim();
+        if let Err(error) = metadata::check_key(key) {
+            return Some(error);
+        }
+        if entry.removed {
+            return None;
+        }
+        if kind == MetadataKind::Label {
+            if let Err(error) = metadata::check_label_value(&entry.value) {
+                return Some(error);
+            }
+        }
+        let duplicate = entries[..index]
+            .iter()
+            .any(|other| !other.removed && other.key.trim() == key);
+        duplicate.then(|| format!("{} is already set above", key))
+    }
+
+    fn has_errors(&self) -> bool {
+        [MetadataKind::Label, MetadataKind::Annotation]
+            .into_iter()
+            .any(|kind| {
+                (0..self.entries(kind).len()).any(|index| self.entry_error(kind, index).is_some())
+            })
+    }
+
+    /// # Returns
+    /// The changes from what the object had, or `None` if it hasn't been loaded yet
+    pub fn patch(&self) -> Option<MetadataPatch> {
+        let original = self.original.as_ref()?;
+        let selection = matches!(self.target, Target::Selection(..));
+        Some(MetadataPatch {
+            labels: changes(&original.labels, &self.labels, selection),
+            annotations: changes(&original.annotations, &self.annotations, selection),
+        })
+    }
+
+    /// Whether the changes are valid, and confirmed if the context is protected
+    pub fn can_apply(&self, context: &KubeContext) -> bool {
+        let confirmed = match &self.target {
+            Target::Object(..) => context.is_confirmed(&self.confirmation_field_value),
+            // a selection is confirmed like every other batch action
+            Target::Selection(..) => true,
+        };
+        !context.is_read_only()
+            && confirmed
+            && !self.applying
+            && !self.has_errors()
+            && self.patch().is_some_and(|patch| !patch.is_empty())
+    }
+
+    fn message(&self, message: MetadataEditorMessage) -> Message {
+        Message::ClusterMessage(self.tab_id, ClusterMessage::MetadataEditorMessage(message))
+    }
+
+    pub fn view(&self, context: &KubeContext, palette: Palette) -> Element<'_, Message> {
+        let title = match &self.target {
+            Target::Object(cluster_object) => format!(
+                "Labels and annotations of {} {}",
+                cluster_object.r#type, cluster_object.name
+            ),
+            Target::Selection(count) => {
+                format!("Labels and annotations of {} selected objects", count)
+            }
+        };
+        let mut apply_button = button(container(text("Apply")).padding(Padding {
+            bottom: 0.0,
+            top: 0.0,
+            left: sizes::SEP,
+            right: sizes::SEP,
+        }))
+        .style(as_button_theme(ButtonTheme::Primary));
+        if self.can_apply(context) {
+            apply_button = apply_button.on_press(Message::ClusterMessage(
+                self.tab_id,
+                ClusterMessage::MetadataApplyRequested,
+            ));
+        }
+        let mut header = row![
+            text(title).style(palette.text),
+            horizontal_space(Length::Fill),
+        ]
+        .spacing(sizes::SEP)
+        .align_items(Alignment::Center);
+        if let (Target::Object(..), Safety::Protected) = (&self.target, context.get_safety()) {
+            header = header.push(
+                text_input(
+                    &format!("Type {} to confirm", context.get_name()),
+                    &self.confirmation_field_value,
+                )
+                .on_input(|value| {
+                    self.message(MetadataEditorMessage::ConfirmationFieldChanged(value))
+                })
+                .width(300),
+            );
+        }
+        header = header
+            .push(
+                button(container(text("Close")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(Message::ClusterMessage(
+                    self.tab_id,
+                    ClusterMessage::MetadataEditorCancelled,
+                ))
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            )
+            .push(apply_button);
+
+        let body: Element<Message> = if self.original.is_none() {
+            text("Loading labels and annotations...")
+                .style(palette.secondary_text)
+                .into()
+        } else {
+            let mut body = column![
+                self.section("Labels", MetadataKind::Label, palette),
+                self.section("Annotations", MetadataKind::Annotation, palette),
+            ]
+            .spacing(sizes::SEP);
+            if let Target::Selection(..) = self.target {
+                body = body.push(
+                    text("Only the keys entered here are changed. Removed keys are removed from every selected object.")
+                        .size(sizes::P)
+                        .style(palette.secondary_text),
+                );
+            }
+            scrollable(body).height(300).into()
+        };
+
+        container(column![header, body].spacing(sizes::SEP))
+            .width(Length::Fill)
+            .padding(sizes::SEP)
+            .style(as_container_theme(ContainerTheme::Light))
+            .into()
+    }
+
+    fn section(&self, title: &str, kind: MetadataKind, palette: Palette) -> Element<'_, Message> {
+        let value_placeholder = match kind {
+            MetadataKind::Label => "Value",
+            MetadataKind::Annotation => "Value, which can be any text",
+        };
+        let mut section =
+            column![text(title).size(sizes::H2).style(palette.text)].spacing(sizes::SMOL_SEP);
+        for (index, entry) in self.entries(kind).iter().enumerate() {
+            // removed entries can't be edited until they're brought back
+            let mut key_input =
+                text_input("Key, e.g. app.kubernetes.io/name", &entry.key).width(300);
+            let mut value_input = text_input(value_placeholder, &entry.value);
+            if !entry.removed {
+                key_input = key_input.on_input(move |value| {
+                    self.message(MetadataEditorMessage::KeyChanged(kind, index, value))
+                });
+                value_input = value_input.on_input(move |value| {
+                    self.message(MetadataEditorMessage::ValueChanged(kind, index, value))
+                });
+            }
+            section = section.push(
+                row![
+                    key_input,
+                    value_input,
+                    button(
+                        container(text(if entry.removed { "Restore" } else { "Remove" })).padding(
+                            Padding {
+                                bottom: 0.0,
+                                top: 0.0,
+                                left: sizes::SEP,
+                                right: sizes::SEP,
+                            }
+                        )
+                    )
+                    .on_press(self.message(MetadataEditorMessage::RemoveToggled(kind, index)))
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                ]
+                .spacing(sizes::SEP)
+                .align_items(Alignment::Center),
+            );
+            if let Some(error) = self.entry_error(kind, index) {
+                section = section.push(text(error).size(sizes::P).style(palette.danger));
+            }
+        }
+        section
+            .push(
+                button(container(text("Add")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(self.message(MetadataEditorMessage::AddRequested(kind)))
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            )
+            .into()
+    }
+}
+
+/// # Returns
+/// What has to be set and removed to go from `original` to `entries`
+fn changes(
+    original: &BTreeMap<String, String>,
+    entries: &[Entry],
+    selection: bool,
+) -> BTreeMap<String, Option<String>> {
+    let kept: BTreeMap<&str, &str> = entries
+        .iter()
+        .filter(|entry| !entry.removed && !entry.is_blank())
+        .map(|entry| (entry.key.trim(), entry.value.as_str()))
+        .collect();
+    let mut changes: BTreeMap<String, Option<String>> = original
+        .keys()
+        .filter(|key| !kept.contains_key(key.as_str()))
+        .map(|key| (key.clone(), None))
+        .collect();
+    // nothing is known about the selected objects, so removed keys are always removed
+    if selection {
+        changes.extend(
+            entries
+                .iter()
+                .filter(|entry| entry.removed && !entry.is_blank())
+                .map(|entry| entry.key.trim())
+                .filter(|key| !kept.contains_key(key))
+                .map(|key| (key.to_owned(), None)),
+        );
+    }
+    changes.extend(
+        kept.into_iter()
+            .filter(|(key, value)| original.get(*key).map(String::as_str) != Some(*value))
+            .map(|(key, value)| (key.to_owned(), Some(value.to_owned()))),
+    );
+    changes
+}
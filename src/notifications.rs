@@ -0,0 +1,206 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use chrono::{DateTime, Local};
+use iced::{
+    widget::{button, column, container, horizontal_space, pick_list, row, scrollable, text},
+    Alignment, Command, Element, Font, Length, Padding,
+};
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    colours::Palette,
+    constants,
+    container_theme::{as_container_theme, ContainerTheme},
+    custom_widgets::toast::{Status, Toast},
+    messages::{Message, NotificationCentreMessage},
+    sizes,
+};
+
+/// A toast that was shown, and when it was shown
+#[derive(Debug, Clone)]
+struct Notification {
+    toast: Toast,
+    received: DateTime<Local>,
+}
+
+/// Which notifications are listed in the notification centre
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeverityFilter {
+    All,
+    Errors,
+    Successes,
+    Information,
+}
+
+impl SeverityFilter {
+    pub const ALL: &'static [Self] = &[Self::All, Self::Errors, Self::Successes, Self::Information];
+
+    fn matches(self, status: Status) -> bool {
+        match self {
+            Self::All => true,
+            Self::Errors => status == Status::Danger,
+            Self::Successes => status == Status::Success,
+            Self::Information => matches!(status, Status::Primary | Status::Secondary),
+        }
+    }
+}
+
+impl Display for SeverityFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => write!(f, "All"),
+            Self::Errors => write!(f, "Errors"),
+            Self::Successes => write!(f, "Successes"),
+            Self::Information => write!(f, "Information"),
+        }
+    }
+}
+
+/// Keeps the toasts that were shown, so that they can be read after they've disappeared.
+/// Only the most recent `constants::NOTIFICATION_HISTORY` are kept.
+#[derive(Debug, Clone)]
+pub struct NotificationCentre {
+    notifications: VecDeque<Notification>,
+    /// Number of notifications that arrived since the notification centre was last opened
+    unread: usize,
+    filter: SeverityFilter,
+}
+
+impl Default for NotificationCentre {
+    fn default() -> Self {
+        NotificationCentre {
+            notifications: VecDeque::new(),
+            unread: 0,
+            filter: SeverityFilter::All,
+        }
+    }
+}
+
+impl NotificationCentre {
+    pub fn push(&mut self, toast: Toast) {
+        if self.notifications.len() == constants::NOTIFICATION_HISTORY {
+            self.notifications.pop_front();
+        }
+        self.notifications.push_back(Notification {
+            toast,
+            received: Local::now(),
+        });
+        self.unread = (self.unread + 1).min(self.notifications.len());
+    }
+
+    pub fn get_unread(&self) -> usize {
+        self.unread
+    }
+
+    pub fn mark_read(&mut self) {
+        self.unread = 0;
+    }
+
+    pub fn update(&mut self, message: NotificationCentreMessage) -> Command<Message> {
+        match message {
+            NotificationCentreMessage::FilterSelected(filter) => {
+                self.filter = filter;
+
+                Command::none()
+            }
+            NotificationCentreMessage::ClearRequested => {
+                self.notifications.clear();
+                self.unread = 0;
+
+                Command::none()
+            }
+        }
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let notifications: Vec<Element<Message>> = self
+            .notifications
+            .iter()
+            .rev()
+            .filter(|notification| self.filter.matches(notification.toast.status))
+            .map(|notification| notification_view(notification, palette))
+            .collect();
+        let list: Element<Message> = if notifications.is_empty() {
+            text("There are no notifications")
+                .style(palette.secondary_text)
+                .into()
+        } else {
+            scrollable(column(notifications).spacing(sizes::SEP))
+                .height(Length::Fill)
+                .into()
+        };
+
+        container(
+            column![
+                row![
+                    text("Notifications").size(sizes::H2),
+                    horizontal_space(Length::Fill),
+                    pick_list(SeverityFilter::ALL, Some(self.filter), |filter| {
+                        NotificationCentreMessage::FilterSelected(filter).into()
+                    }),
+                    button(container(text("Clear")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .on_press(NotificationCentreMessage::ClearRequested.into())
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                    button(container(text("Close")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .on_press(Message::NotificationsClosed)
+                    .style(as_button_theme(ButtonTheme::Primary)),
+                ]
+                .spacing(sizes::SEP)
+                .align_items(Alignment::Center),
+                list,
+            ]
+            .max_width(800)
+            .spacing(sizes::SEP),
+        )
+        .style(as_container_theme(ContainerTheme::Light))
+        .padding(sizes::SEP)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .into()
+    }
+}
+
+fn notification_view(notification: &Notification, palette: Palette) -> Element<'_, Message> {
+    let toast = &notification.toast;
+    let mut content = column![
+        row![
+            text(&toast.title).style(toast.status.text_colour(&palette)),
+            horizontal_space(Length::Fill),
+            text(
+                notification
+                    .received
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            )
+            .size(sizes::P * 3.0 / 4.0)
+            .style(palette.secondary_text),
+        ]
+        .align_items(Alignment::Center),
+        text(&toast.body).size(sizes::P),
+    ]
+    .spacing(sizes::SMOL_SEP);
+    if let Some(details) = &toast.details {
+        content = content.push(
+            text(details)
+                .font(Font::MONOSPACE)
+                .size(sizes::P * 3.0 / 4.0)
+                .style(palette.secondary_text),
+        );
+    }
+    container(content)
+        .width(Length::Fill)
+        .padding(sizes::SEP)
+        .style(iced::theme::Container::Custom(Box::new(toast.status)))
+        .into()
+}
@@ -0,0 +1,319 @@
+use iced::{
+    widget::{
+        button, column, container, horizontal_space, row, scrollable, text, text_input,
+        vertical_rule, Column, Row,
+    },
+    Alignment, Command, Element, Length, Padding,
+};
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    kube_context::KubeContext,
+    kube_interface,
+    messages::{Message, PermissionExplorerMessage},
+    permissions::{Access, AccessQuery, Rules, VERBS},
+    sizes,
+    tab::TabId,
+    utils,
+};
+
+const RESOURCE_WIDTH: f32 = 300.0;
+const VERB_WIDTH: f32 = 110.0;
+
+/// Shows what the user may do in a namespace, and answers questions about single actions
+#[derive(Debug, Clone)]
+pub struct PermissionExplorer {
+    id: TabId,
+    context: KubeContext,
+    rules: Option<Rules>,
+    verb_field_value: String,
+    resource_field_value: String,
+    subresource_field_value: String,
+    name_field_value: String,
+    /// The last question that was asked, along with the answer
+    answer: Option<(AccessQuery, Access)>,
+}
+
+impl PermissionExplorer {
+    pub fn new(id: TabId, context: KubeContext) -> PermissionExplorer {
+        PermissionExplorer {
+            id,
+            context,
+            rules: None,
+            verb_field_value: String::from(""),
+            resource_field_value: String::from(""),
+            subresource_field_value: String::from(""),
+            name_field_value: String::from(""),
+            answer: None,
+        }
+    }
+
+    pub fn get_id(&self) -> TabId {
+        self.id
+    }
+
+    pub fn get_context(&self) -> &KubeContext {
+        &self.context
+    }
+
+    /// Wraps the given message so that it's routed back to this tab
+    fn message(&self, message: PermissionExplorerMessage) -> Message {
+        Message::PermissionExplorerMessage(self.id, message)
+    }
+
+    pub fn update(&mut self, message: PermissionExplorerMessage) -> Command<Message> {
+        let id = self.id;
+        match message {
+            PermissionExplorerMessage::ReloadRequested => {
+                // rules can only be reviewed for a single namespace
+                if self.context.is_all_namespaces() {
+                    return Command::none();
+                }
+                Command::perform(
+                    kube_interface::fetch_rules(self.context.clone()),
+                    move |res| {
+                        Message::PermissionExplorerMessage(
+                            id,
+                            PermissionExplorerMessage::RulesLoaded(res),
+                        )
+                    },
+                )
+            }
+            PermissionExplorerMessage::RulesLoaded(Ok(rules)) => {
+                self.rules = Some(rules);
+
+                Command::none()
+            }
+            PermissionExplorerMessage::RulesLoaded(Err(error)) if error.is_auth_expired() => {
+                let context_name = self.context.get_name();
+                Command::perform(utils::resolved(), move |_ignored| {
+                    Message::AuthExpired(context_name)
+                })
+            }
+            PermissionExplorerMessage::RulesLoaded(Err(error)) => {
+                tracing::warn!("Unable to review permissions: {}", error.get_message());
+                let toast = error.to_toast("Unable to find out what you can do in this namespace.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            PermissionExplorerMessage::VerbFieldChanged(value) => {
+                self.verb_field_value = value;
+
+                Command::none()
+            }
+            PermissionExplorerMessage::ResourceFieldChanged(value) => {
+                self.resource_field_value = value;
+
+                Command::none()
+            }
+            PermissionExplorerMessage::SubresourceFieldChanged(value) => {
+                self.subresource_field_value = value;
+
+                Command::none()
+            }
+            PermissionExplorerMessage::NameFieldChanged(value) => {
+                self.name_field_value = value;
+
+                Command::none()
+            }
+            PermissionExplorerMessage::CanIRequested => {
+                let query = AccessQuery::new(
+                    &self.verb_field_value,
+                    &self.resource_field_value,
+                    &self.subresource_field_value,
+                    &self.name_field_value,
+                );
+                if query.verb.is_empty() || query.resource.is_empty() {
+                    return Command::none();
+                }
+                Command::perform(
+                    kube_interface::can_i(self.context.clone(), query.clone()),
+                    move |res| {
+                        Message::PermissionExplorerMessage(
+                            id,
+                            PermissionExplorerMessage::CanIAnswered(query, res),
+                        )
+                    },
+                )
+            }
+            PermissionExplorerMessage::CanIAnswered(query, Ok(access)) => {
+                self.answer = Some((query, access));
+
+                Command::none()
+            }
+            PermissionExplorerMessage::CanIAnswered(_, Err(error)) => {
+                tracing::warn!("Unable to review access: {}", error.get_message());
+                let toast = error.to_toast("Unable to ask the cluster.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+        }
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let header = container(
+            row![
+                text("Permissions").size(sizes::H2).style(palette.on_accent),
+                vertical_rule(sizes::P),
+                self.context.view(palette),
+                horizontal_space(Length::Fill),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(Padding {
+            bottom: sizes::SEP,
+            top: sizes::SEP,
+            left: 2.0 * sizes::SEP,
+            right: 2.0 * sizes::SEP,
+        })
+        .style(as_container_theme(ContainerTheme::Dark));
+
+        let matrix: Element<Message> = if self.context.is_all_namespaces() {
+            text("Open a single namespace to see what you can do in it. You can still ask about the whole cluster above.")
+                .style(palette.secondary_text)
+                .into()
+        } else {
+            match &self.rules {
+                Some(rules) => matrix(rules, palette),
+                None => text("Reviewing permissions...").style(palette.text).into(),
+            }
+        };
+
+        column![
+            header,
+            container(
+                column![
+                    self.can_i_view(palette),
+                    scrollable(matrix).height(Length::Fill)
+                ]
+                .spacing(sizes::SEP)
+            )
+            .padding(sizes::SEP)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(as_container_theme(ContainerTheme::Light))
+        ]
+        .into()
+    }
+
+    /// Asks whether a single action is allowed, like `kubectl auth can-i` does
+    fn can_i_view(&self, palette: Palette) -> Element<'_, Message> {
+        let answer: Element<Message> = match &self.answer {
+            Some((query, access)) => {
+                let mut answer =
+                    format!("{}: {}", query, if access.allowed { "yes" } else { "no" });
+                if let Some(reason) = &access.reason {
+                    answer.push_str(&format!(" ({})", reason));
+                }
+                text(answer)
+                    .style(if access.allowed {
+                        palette.success
+                    } else {
+                        palette.danger
+                    })
+                    .into()
+            }
+            None => text("Ask whether you can do something, e.g. delete deployments.apps")
+                .style(palette.secondary_text)
+                .into(),
+        };
+
+        column![
+            row![
+                text_input("Verb", &self.verb_field_value)
+                    .on_input(
+                        |value| self.message(PermissionExplorerMessage::VerbFieldChanged(value))
+                    )
+                    .on_submit(self.message(PermissionExplorerMessage::CanIRequested)),
+                text_input(
+                    "Resource, e.g. deployments.apps",
+                    &self.resource_field_value
+                )
+                .on_input(|value| {
+                    self.message(PermissionExplorerMessage::ResourceFieldChanged(value))
+                })
+                .on_submit(self.message(PermissionExplorerMessage::CanIRequested)),
+                text_input("Subresource (optional)", &self.subresource_field_value)
+                    .on_input(|value| {
+                        self.message(PermissionExplorerMessage::SubresourceFieldChanged(value))
+                    })
+                    .on_submit(self.message(PermissionExplorerMessage::CanIRequested)),
+                text_input("Name (optional)", &self.name_field_value)
+                    .on_input(
+                        |value| self.message(PermissionExplorerMessage::NameFieldChanged(value))
+                    )
+                    .on_submit(self.message(PermissionExplorerMessage::CanIRequested)),
+                button(container(text("Can I?")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(self.message(PermissionExplorerMessage::CanIRequested))
+                .style(as_button_theme(ButtonTheme::Primary)),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+            answer,
+        ]
+        .spacing(sizes::SMOL_SEP)
+        .into()
+    }
+}
+
+/// Lays out which verbs are allowed on each resource
+fn matrix(rules: &Rules, palette: Palette) -> Element<'_, Message> {
+    let mut heading = Row::new().push(
+        text("Resource")
+            .size(sizes::P)
+            .width(RESOURCE_WIDTH)
+            .style(palette.secondary_text),
+    );
+    for verb in VERBS {
+        heading = heading.push(
+            text(verb)
+                .size(sizes::P)
+                .width(VERB_WIDTH)
+                .style(palette.secondary_text),
+        );
+    }
+
+    let mut rows: Vec<Element<Message>> = vec![heading.into()];
+    rows.extend(rules.get_resources().into_iter().map(|(group, resource)| {
+        let label = if group.is_empty() || group == "*" && resource == "*" {
+            resource.clone()
+        } else {
+            format!("{}.{}", resource, group)
+        };
+        let mut row = Row::new().push(
+            text(label)
+                .size(sizes::P)
+                .width(RESOURCE_WIDTH)
+                .style(palette.text),
+        );
+        for verb in VERBS {
+            let (cell, colour) = if rules.allows(verb, &group, &resource, None) == Some(true) {
+                ("✓", palette.success)
+            } else if rules.allows_some_names(verb, &group, &resource) {
+                ("some names", palette.secondary_text)
+            } else {
+                ("", palette.text)
+            };
+            row = row.push(text(cell).size(sizes::P).width(VERB_WIDTH).style(colour));
+        }
+        row.align_items(Alignment::Center).into()
+    }));
+
+    let mut matrix = Column::with_children(rows).spacing(sizes::SMOL_SEP);
+    if rules.is_incomplete() {
+        matrix = matrix.push(
+            text("The cluster couldn't list every rule, so you may be able to do more than this shows. Ask about single actions above to be sure.")
+                .style(palette.secondary_text),
+        );
+    }
+    matrix.into()
+}
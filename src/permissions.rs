@@ -0,0 +1,286 @@
+//! What the current user is allowed to do, according to the API server
+
+use k8s_openapi::api::authorization::v1::{
+    ResourceRule, SubjectAccessReviewStatus, SubjectRulesReviewStatus,
+};
+
+/// The verbs that are shown for each resource, in the order `kubectl` lists them
+pub const VERBS: [&str; 8] = [
+    "get",
+    "list",
+    "watch",
+    "create",
+    "update",
+    "patch",
+    "delete",
+    "deletecollection",
+];
+
+/// The rules that apply to the current user in one namespace, from a `SelfSubjectRulesReview`
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    resource_rules: Vec<ResourceRule>,
+    /// Set when the API server couldn't list every rule, e.g. because a webhook authorizer is used.
+    /// Anything that isn't allowed by the rules might still be allowed then.
+    incomplete: bool,
+}
+
+impl Rules {
+    pub fn new(status: SubjectRulesReviewStatus) -> Rules {
+        Rules {
+            resource_rules: status.resource_rules,
+            incomplete: status.incomplete,
+        }
+    }
+
+    /// Checks whether the user may `verb` the `resource` in the API `group`.
+    /// `resource` can include a subresource, e.g. `pods/log`.
+    /// If `name` is given, rules that only apply to other names are ignored.
+    ///
+    /// # Returns
+    /// Whether it's allowed, or `None` if the rules don't allow it but might be incomplete
+    pub fn allows(
+        &self,
+        verb: &str,
+        group: &str,
+        resource: &str,
+        name: Option<&str>,
+    ) -> Option<bool> {
+        let allowed = self.resource_rules.iter().any(|rule| {
+            matches(&rule.verbs, verb)
+                && rule
+                    .api_groups
+                    .as_ref()
+                    .is_some_and(|groups| matches(groups, group))
+                && rule
+                    .resources
+                    .as_ref()
+                    .is_some_and(|resources| matches_resource(resources, resource))
+                && match rule.resource_names.as_ref() {
+                    Some(names) if !names.is_empty() => {
+                        name.is_some_and(|name| names.iter().any(|allowed| allowed == name))
+                    }
+                    _ => true,
+                }
+        });
+        match (allowed, self.incomplete) {
+            (true, _) => Some(true),
+            (false, true) => None,
+            (false, false) => Some(false),
+        }
+    }
+
+    /// Whether the user may `verb` some of the `resource`s in the API `group`, but only ones with certain names
+    pub fn allows_some_names(&self, verb: &str, group: &str, resource: &str) -> bool {
+        self.resource_rules.iter().any(|rule| {
+            matches(&rule.verbs, verb)
+                && rule
+                    .api_groups
+                    .as_ref()
+                    .is_some_and(|groups| matches(groups, group))
+                && rule
+                    .resources
+                    .as_ref()
+                    .is_some_and(|resources| matches_resource(resources, resource))
+                && rule
+                    .resource_names
+                    .as_ref()
+                    .is_some_and(|names| !names.is_empty())
+        })
+    }
+
+    /// # Returns
+    /// Every API group and resource that the rules mention, sorted by group and then by resource
+    pub fn get_resources(&self) -> Vec<(String, String)> {
+        let mut resources: Vec<(String, String)> = self
+            .resource_rules
+            .iter()
+            .flat_map(|rule| {
+                let groups = rule.api_groups.clone().unwrap_or_default();
+                let resources = rule.resources.clone().unwrap_or_default();
+                groups.into_iter().flat_map(move |group| {
+                    resources
+                        .clone()
+                        .into_iter()
+                        .map(move |resource| (group.clone(), resource))
+                })
+            })
+            .collect();
+        resources.sort();
+        resources.dedup();
+        resources
+    }
+
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+}
+
+/// A question for the API server about whether the user may do something, like `kubectl auth can-i` asks
+#[derive(Debug, Clone, Default)]
+pub struct AccessQuery {
+    pub verb: String,
+    pub group: String,
+    pub resource: String,
+    pub subresource: Option<String>,
+    pub name: Option<String>,
+}
+
+impl AccessQuery {
+    /// Parses the resource the way `kubectl` writes it, e.g. `deployments.apps` or `pods`
+    pub fn new(verb: &str, resource: &str, subresource: &str, name: &str) -> AccessQuery {
+        let (resource, group) = resource
+            .trim()
+            .split_once('.')
+            .unwrap_or((resource.trim(), ""));
+        let non_empty =
+            |value: &str| Some(value.trim().to_owned()).filter(|value| !value.is_empty());
+        AccessQuery {
+            verb: verb.trim().to_owned(),
+            group: group.to_owned(),
+            resource: resource.to_owned(),
+            subresource: non_empty(subresource),
+            name: non_empty(name),
+        }
+    }
+}
+
+impl std::fmt::Display for AccessQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.verb, self.resource)?;
+        if !self.group.is_empty() {
+            write!(f, ".{}", self.group)?;
+        }
+        if let Some(subresource) = &self.subresource {
+            write!(f, "/{}", subresource)?;
+        }
+        if let Some(name) = &self.name {
+            write!(f, " {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The API server's answer to an `AccessQuery`
+#[derive(Debug, Clone)]
+pub struct Access {
+    pub allowed: bool,
+    /// Why it was allowed or denied, if the authorizer said
+    pub reason: Option<String>,
+}
+
+impl From<SubjectAccessReviewStatus> for Access {
+    fn from(status: SubjectAccessReviewStatus) -> Self {
+        Access {
+            allowed: status.allowed,
+            reason: status.reason.filter(|reason| !reason.is_empty()),
+        }
+    }
+}
+
+fn matches(values: &[String], wanted: &str) -> bool {
+    values.iter().any(|value| value == "*" || value == wanted)
+}
+
+/// Resources can be given as `pods`, `pods/log`, `pods/*`, `*/scale` or `*`
+fn matches_resource(resources: &[String], wanted: &str) -> bool {
+    let (wanted_resource, wanted_subresource) = match wanted.split_once('/') {
+        Some((resource, subresource)) => (resource, Some(subresource)),
+        None => (wanted, None),
+    };
+    resources.iter().any(|resource| {
+        if resource == "*" || resource == wanted {
+            return true;
+        }
+        match (resource.split_once('/'), wanted_subresource) {
+            (Some((resource, subresource)), Some(wanted_subresource)) => {
+                (resource == "*" || resource == wanted_resource)
+                    && (subresource == "*" || subresource == wanted_subresource)
+            }
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resources(resources: &[&str]) -> Vec<String> {
+        resources
+            .iter()
+            .map(|resource| resource.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn matches_resources_by_name() {
+        assert!(matches_resource(&resources(&["pods"]), "pods"));
+        assert!(!matches_resource(&resources(&["pods"]), "deployments"));
+        assert!(matches_resource(
+            &resources(&["deployments", "pods"]),
+            "pods"
+        ));
+    }
+
+    #[test]
+    fn resources_do_not_match_their_subresources() {
+        assert!(!matches_resource(&resources(&["pods"]), "pods/log"));
+        assert!(!matches_resource(&resources(&["pods/log"]), "pods"));
+        assert!(matches_resource(&resources(&["pods/log"]), "pods/log"));
+        assert!(!matches_resource(&resources(&["pods/log"]), "pods/exec"));
+    }
+
+    #[test]
+    fn wildcards_match_resources_and_subresources() {
+        assert!(matches_resource(&resources(&["*"]), "pods"));
+        assert!(matches_resource(&resources(&["*"]), "pods/log"));
+        assert!(matches_resource(&resources(&["pods/*"]), "pods/log"));
+        assert!(!matches_resource(&resources(&["pods/*"]), "pods"));
+        assert!(!matches_resource(&resources(&["pods/*"]), "services/proxy"));
+        assert!(matches_resource(
+            &resources(&["*/scale"]),
+            "deployments/scale"
+        ));
+        assert!(!matches_resource(&resources(&["*/scale"]), "deployments"));
+        assert!(!matches_resource(&resources(&["*/scale"]), "pods/log"));
+    }
+
+    #[test]
+    fn rules_for_some_names_only_allow_those_names() {
+        let rules = Rules::new(SubjectRulesReviewStatus {
+            resource_rules: vec![ResourceRule {
+                verbs: resources(&["delete"]),
+                api_groups: Some(resources(&["apps"])),
+                resources: Some(resources(&["deployments"])),
+                resource_names: Some(resources(&["web"])),
+            }],
+            incomplete: false,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            rules.allows("delete", "apps", "deployments", Some("web")),
+            Some(true)
+        );
+        assert_eq!(
+            rules.allows("delete", "apps", "deployments", Some("db")),
+            Some(false)
+        );
+        assert_eq!(
+            rules.allows("delete", "apps", "deployments", None),
+            Some(false)
+        );
+        assert!(rules.allows_some_names("delete", "apps", "deployments"));
+    }
+
+    #[test]
+    fn incomplete_rules_do_not_forbid() {
+        let rules = Rules::new(SubjectRulesReviewStatus {
+            incomplete: true,
+            ..Default::default()
+        });
+
+        assert_eq!(rules.allows("delete", "", "pods", None), None);
+    }
+}
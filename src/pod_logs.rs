@@ -0,0 +1,151 @@
+use iced::{
+    futures::future::{self, AbortHandle},
+    widget::{column, container, horizontal_space, row, scrollable, text, vertical_rule},
+    Alignment, Command, Element, Font, Length, Padding,
+};
+
+use crate::{
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    kube_context::KubeContext,
+    kube_interface,
+    messages::{Message, PodLogsMessage},
+    sizes,
+    tab::TabId,
+    utils,
+};
+
+/// Shows the end of the log of a single pod
+#[derive(Debug, Clone)]
+pub struct PodLogs {
+    id: TabId,
+    context: KubeContext,
+    pod_name: String,
+    logs: Option<String>,
+    /// Cancels the refresh that's in flight, so that a slow response can't overwrite a newer one
+    reload_handle: Option<AbortHandle>,
+    /// Numbers the refreshes, so that the result of one that was replaced is ignored
+    reload_count: u64,
+}
+
+impl PodLogs {
+    pub fn new(id: TabId, context: KubeContext, pod_name: String) -> PodLogs {
+        PodLogs {
+            id,
+            context,
+            pod_name,
+            logs: None,
+            reload_handle: None,
+            reload_count: 0,
+        }
+    }
+
+    pub fn get_id(&self) -> TabId {
+        self.id
+    }
+
+    pub fn get_context(&self) -> &KubeContext {
+        &self.context
+    }
+
+    pub fn get_pod_name(&self) -> &str {
+        &self.pod_name
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let header = container(
+            row![
+                text(format!("Logs for {}", self.pod_name))
+                    .size(sizes::H2)
+                    .style(palette.on_accent),
+                vertical_rule(sizes::P),
+                self.context.view(palette),
+                horizontal_space(Length::Fill),
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(Padding {
+            bottom: sizes::SEP,
+            top: sizes::SEP,
+            left: 2.0 * sizes::SEP,
+            right: 2.0 * sizes::SEP,
+        })
+        .style(as_container_theme(ContainerTheme::Dark));
+
+        let logs: Element<Message> = match &self.logs {
+            Some(logs) => scrollable(
+                text(logs)
+                    .font(Font::MONOSPACE)
+                    .size(sizes::P)
+                    .style(palette.text),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into(),
+            None => text("Loading logs...").style(palette.text).into(),
+        };
+
+        column![
+            header,
+            container(logs)
+                .padding(sizes::SEP)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(as_container_theme(ContainerTheme::Light))
+        ]
+        .into()
+    }
+
+    pub fn update(&mut self, message: PodLogsMessage) -> Command<Message> {
+        let id = self.id;
+        if let PodLogsMessage::LogsLoaded(reload, ..) = message {
+            // a refresh can finish before it's cancelled, and its result mustn't be used then
+            if reload != self.reload_count {
+                return Command::none();
+            }
+            self.reload_handle = None;
+        }
+        match message {
+            PodLogsMessage::ReloadRequested => {
+                if let Some(reload_handle) = self.reload_handle.take() {
+                    reload_handle.abort();
+                }
+                self.reload_count = self.reload_count.wrapping_add(1);
+                let reload = self.reload_count;
+                let (fetch, reload_handle) = future::abortable(kube_interface::fetch_pod_logs(
+                    self.context.clone(),
+                    self.pod_name.clone(),
+                ));
+                self.reload_handle = Some(reload_handle);
+
+                Command::perform(fetch, move |res| match res {
+                    Ok(res) => Message::PodLogsMessage(id, PodLogsMessage::LogsLoaded(reload, res)),
+                    Err(..) => Message::PodLogsMessage(id, PodLogsMessage::ReloadCancelled),
+                })
+            }
+            PodLogsMessage::ReloadCancelled => Command::none(),
+            PodLogsMessage::LogsLoaded(_, Ok(logs)) => {
+                self.logs = Some(logs);
+
+                Command::none()
+            }
+            PodLogsMessage::LogsLoaded(_, Err(error)) if error.is_auth_expired() => {
+                let context_name = self.context.get_name();
+                Command::perform(utils::resolved(), move |_ignored| {
+                    Message::AuthExpired(context_name)
+                })
+            }
+            PodLogsMessage::LogsLoaded(_, Err(error)) => {
+                tracing::warn!("Unable to read pod logs: {}", error.get_message());
+                // the pod was probably deleted, so keep showing the last logs that were read
+                if self.logs.is_none() {
+                    self.logs = Some(String::from("The logs for this pod couldn't be read."));
+                }
+
+                Command::none()
+            }
+        }
+    }
+}
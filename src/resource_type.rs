@@ -1,4 +1,13 @@
-#[derive(Debug, Clone)]
+use std::{fmt::Display, str::FromStr};
+
+use k8s_openapi::api::{
+    apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+    core::v1::Pod,
+};
+use kube::core::ApiResource;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ResourceType {
     Pod,
     Deployment,
@@ -7,14 +16,67 @@ pub enum ResourceType {
     DaemonSet,
 }
 
-impl ToString for ResourceType {
-    fn to_string(&self) -> String {
+impl ResourceType {
+    pub const ALL: [ResourceType; 5] = [
+        Self::Pod,
+        Self::Deployment,
+        Self::ReplicaSet,
+        Self::StatefulSet,
+        Self::DaemonSet,
+    ];
+
+    /// Whether the pods of the resource can be restarted, like `kubectl rollout restart` does
+    pub fn can_restart(&self) -> bool {
+        matches!(self, Self::Deployment | Self::StatefulSet | Self::DaemonSet)
+    }
+
+    /// Whether the resource has a number of replicas that can be changed
+    pub fn can_scale(&self) -> bool {
+        matches!(
+            self,
+            Self::Deployment | Self::StatefulSet | Self::ReplicaSet
+        )
+    }
+
+    /// Describes where the resource type lives in the API, so that it can be used through a `DynamicObject`
+    pub fn api_resource(&self) -> ApiResource {
         match &self {
-            Self::Pod => String::from("Pod"),
-            Self::Deployment => String::from("Deployment"),
-            Self::ReplicaSet => String::from("ReplicaSet"),
-            Self::DaemonSet => String::from("DaemonSet"),
-            Self::StatefulSet => String::from("StatefulSet"),
+            Self::Pod => ApiResource::erase::<Pod>(&()),
+            Self::Deployment => ApiResource::erase::<Deployment>(&()),
+            Self::ReplicaSet => ApiResource::erase::<ReplicaSet>(&()),
+            Self::DaemonSet => ApiResource::erase::<DaemonSet>(&()),
+            Self::StatefulSet => ApiResource::erase::<StatefulSet>(&()),
+        }
+    }
+}
+
+impl Display for ResourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Self::Pod => write!(f, "Pod"),
+            Self::Deployment => write!(f, "Deployment"),
+            Self::ReplicaSet => write!(f, "ReplicaSet"),
+            Self::DaemonSet => write!(f, "DaemonSet"),
+            Self::StatefulSet => write!(f, "StatefulSet"),
+        }
+    }
+}
+
+impl FromStr for ResourceType {
+    type Err = String;
+
+    /// Accepts the same names as `kubectl`, e.g. `pod`, `pods` or `po`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "pod" | "pods" | "po" => Ok(Self::Pod),
+            "deployment" | "deployments" | "deploy" => Ok(Self::Deployment),
+            "replicaset" | "replicasets" | "rs" => Ok(Self::ReplicaSet),
+            "statefulset" | "statefulsets" | "sts" => Ok(Self::StatefulSet),
+            "daemonset" | "daemonsets" | "ds" => Ok(Self::DaemonSet),
+            _ => Err(format!(
+                "unknown resource type '{}', expected one of pods, deployments, replicasets, statefulsets or daemonsets",
+                name
+            )),
         }
     }
 }
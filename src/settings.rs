@@ -0,0 +1,375 @@
+use std::fmt::Display;
+
+use iced::{
+    widget::{
+        button, checkbox, column, container, horizontal_space, pick_list, row, text, text_input,
+    },
+    Alignment, Command, Element, Length, Padding,
+};
+use kube::api::PropagationPolicy;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    colours::Palette,
+    constants,
+    container_theme::{as_container_theme, ContainerTheme},
+    custom_widgets::toast::{Status, Toast},
+    messages::{Message, SettingsEditorMessage},
+    sizes, utils,
+};
+
+/// Preferences that the user can change from the settings screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// Seconds between refreshes of the cluster state
+    pub refresh_interval: u64,
+    /// Seconds before a notification disappears
+    pub toast_timeout: u64,
+    pub theme: ThemeChoice,
+    /// What happens to the dependents of a resource when it's deleted
+    pub propagation_policy: DeletePropagation,
+    /// Whether the user is asked to confirm before a resource is deleted
+    pub confirm_delete: bool,
+    /// Contexts whose names match any of these patterns are protected, so changes to them have to be
+    /// confirmed by typing the name of the context. `*` matches any number of characters.
+    pub protected_contexts: Vec<String>,
+}
+
+impl UserSettings {
+    pub fn is_protected_context(&self, context_name: &str) -> bool {
+        self.protected_contexts
+            .iter()
+            .any(|pattern| matches_pattern(pattern, context_name))
+    }
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        UserSettings {
+            refresh_interval: constants::CLUSTER_REFRESH,
+            toast_timeout: constants::TOAST_TIMEOUT,
+            theme: ThemeChoice::Light,
+            propagation_policy: DeletePropagation::Background,
+            confirm_delete: false,
+            protected_contexts: vec![String::from("*prod*")],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl ThemeChoice {
+    pub const ALL: &'static [Self] = &[Self::Light, Self::Dark, Self::HighContrast];
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Self::Light => Palette::LIGHT,
+            Self::Dark => Palette::DARK,
+            Self::HighContrast => Palette::HIGH_CONTRAST,
+        }
+    }
+}
+
+impl Display for ThemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Light => write!(f, "Light"),
+            Self::Dark => write!(f, "Dark"),
+            Self::HighContrast => write!(f, "High contrast"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeletePropagation {
+    Background,
+    Foreground,
+    Orphan,
+}
+
+impl DeletePropagation {
+    pub const ALL: &'static [Self] = &[Self::Background, Self::Foreground, Self::Orphan];
+
+    pub fn to_kube(self) -> PropagationPolicy {
+        match self {
+            Self::Background => PropagationPolicy::Background,
+            Self::Foreground => PropagationPolicy::Foreground,
+            Self::Orphan => PropagationPolicy::Orphan,
+        }
+    }
+}
+
+impl Display for DeletePropagation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Background => write!(f, "Background"),
+            Self::Foreground => write!(f, "Foreground"),
+            Self::Orphan => write!(f, "Orphan"),
+        }
+    }
+}
+
+/// Screen for changing the user's settings
+#[derive(Debug, Clone)]
+pub struct SettingsEditor {
+    settings: UserSettings,
+    refresh_interval_value: String,
+    toast_timeout_value: String,
+    /// Comma separated
+    protected_contexts_value: String,
+}
+
+impl SettingsEditor {
+    pub fn new(settings: UserSettings) -> SettingsEditor {
+        SettingsEditor {
+            refresh_interval_value: settings.refresh_interval.to_string(),
+            toast_timeout_value: settings.toast_timeout.to_string(),
+            protected_contexts_value: settings.protected_contexts.join(", "),
+            settings,
+        }
+    }
+
+    pub fn update(&mut self, message: SettingsEditorMessage) -> Command<Message> {
+        match message {
+            SettingsEditorMessage::RefreshIntervalChanged(value) => {
+                self.refresh_interval_value = value;
+
+                Command::none()
+            }
+            SettingsEditorMessage::ToastTimeoutChanged(value) => {
+                self.toast_timeout_value = value;
+
+                Command::none()
+            }
+            SettingsEditorMessage::ProtectedContextsChanged(value) => {
+                self.protected_contexts_value = value;
+
+                Command::none()
+            }
+            SettingsEditorMessage::ThemeSelected(theme) => {
+                self.settings.theme = theme;
+
+                Command::none()
+            }
+            SettingsEditorMessage::PropagationPolicySelected(propagation_policy) => {
+                self.settings.propagation_policy = propagation_policy;
+
+                Command::none()
+            }
+            SettingsEditorMessage::ConfirmDeleteToggled(confirm_delete) => {
+                self.settings.confirm_delete = confirm_delete;
+
+                Command::none()
+            }
+            SettingsEditorMessage::SaveRequested => {
+                let (Some(refresh_interval), Some(toast_timeout)) = (
+                    parse_seconds(&self.refresh_interval_value),
+                    parse_seconds(&self.toast_timeout_value),
+                ) else {
+                    return Command::perform(utils::resolved(), |_ignored| {
+                        Message::AddToast(Toast::new(
+                            Status::Danger,
+                            "Error",
+                            "The refresh interval and notification timeout must be a whole number of seconds",
+                        ))
+                    });
+                };
+                let protected_contexts = self
+                    .protected_contexts_value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+                let settings = UserSettings {
+                    refresh_interval,
+                    toast_timeout,
+                    protected_contexts,
+                    ..self.settings.clone()
+                };
+
+                Command::perform(utils::resolved(), move |_ignored| {
+                    Message::SettingsSaved(settings)
+                })
+            }
+        }
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        container(
+            column![
+                text("Settings").size(sizes::H2).style(palette.text),
+                setting(
+                    "Refresh interval (seconds)",
+                    text_input("4", &self.refresh_interval_value)
+                        .on_input(
+                            |value| SettingsEditorMessage::RefreshIntervalChanged(value).into()
+                        )
+                        .into(),
+                    palette
+                ),
+                setting(
+                    "Notification timeout (seconds)",
+                    text_input("10", &self.toast_timeout_value)
+                        .on_input(|value| SettingsEditorMessage::ToastTimeoutChanged(value).into())
+                        .into(),
+                    palette
+                ),
+                setting(
+                    "Theme",
+                    pick_list(ThemeChoice::ALL, Some(self.settings.theme), |theme| {
+                        SettingsEditorMessage::ThemeSelected(theme).into()
+                    })
+                    .width(Length::Fill)
+                    .into(),
+                    palette
+                ),
+                setting(
+                    "Delete propagation policy",
+                    pick_list(
+                        DeletePropagation::ALL,
+                        Some(self.settings.propagation_policy),
+                        |propagation_policy| {
+                            SettingsEditorMessage::PropagationPolicySelected(propagation_policy)
+                                .into()
+                        }
+                    )
+                    .width(Length::Fill)
+                    .into(),
+                    palette
+                ),
+                setting(
+                    "Protected contexts (patterns, separated by commas). Changes in them are confirmed by typing the context's name",
+                    text_input("*prod*", &self.protected_contexts_value)
+                        .on_input(
+                            |value| SettingsEditorMessage::ProtectedContextsChanged(value).into()
+                        )
+                        .into(),
+                    palette
+                ),
+                checkbox(
+                    "Confirm before deleting",
+                    self.settings.confirm_delete,
+                    |confirm_delete| SettingsEditorMessage::ConfirmDeleteToggled(confirm_delete)
+                        .into()
+                ),
+                row![
+                    button(container(text("Cancel")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .on_press(Message::SettingsClosed)
+                    .style(as_button_theme(ButtonTheme::Secondary)),
+                    horizontal_space(Length::Fill),
+                    button(container(text("Save")).padding(Padding {
+                        bottom: 0.0,
+                        top: 0.0,
+                        left: sizes::SEP,
+                        right: sizes::SEP,
+                    }))
+                    .on_press(SettingsEditorMessage::SaveRequested.into())
+                    .style(as_button_theme(ButtonTheme::Primary)),
+                ],
+            ]
+            .max_width(400)
+            .spacing(sizes::SEP),
+        )
+        .style(as_container_theme(ContainerTheme::Light))
+        .padding(sizes::SEP)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .into()
+    }
+}
+
+fn setting<'a>(
+    label: &'a str,
+    input: Element<'a, Message>,
+    palette: Palette,
+) -> Element<'a, Message> {
+    row![
+        text(label).style(palette.text).width(Length::Fill),
+        container(input).width(Length::Fill),
+    ]
+    .spacing(sizes::SEP)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+/// # Returns
+/// The number of seconds in `value`, or `None` if it isn't a positive whole number
+fn parse_seconds(value: &str) -> Option<u64> {
+    value.trim().parse().ok().filter(|seconds| *seconds > 0)
+}
+
+/// Matches `value` against a pattern where `*` matches any number of characters, ignoring case
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let value = value.to_lowercase();
+    let mut parts = pattern.split('*');
+    // split always returns at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // there was no `*`, so the whole value has to match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_whole_value_without_a_wildcard() {
+        assert!(matches_pattern("prod", "prod"));
+        assert!(!matches_pattern("prod", "production"));
+        assert!(!matches_pattern("prod", "my-prod"));
+    }
+
+    #[test]
+    fn wildcards_match_any_number_of_characters() {
+        assert!(matches_pattern("*prod*", "prod"));
+        assert!(matches_pattern("*prod*", "eu-prod-1"));
+        assert!(matches_pattern("prod-*", "prod-eu"));
+        assert!(matches_pattern("*-prod", "eu-prod"));
+        assert!(matches_pattern("eu-*-prod", "eu-west-prod"));
+        assert!(matches_pattern("*", ""));
+        assert!(!matches_pattern("prod-*", "eu-prod"));
+        assert!(!matches_pattern("*-prod", "prod-eu"));
+    }
+
+    #[test]
+    fn parts_of_the_pattern_do_not_overlap() {
+        assert!(!matches_pattern("a*a", "a"));
+        assert!(matches_pattern("a*a", "aa"));
+        assert!(!matches_pattern("*ab*ba*", "aba"));
+        assert!(matches_pattern("*ab*ba*", "abba"));
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert!(matches_pattern("*PROD*", "eu-prod"));
+        assert!(matches_pattern("*prod*", "EU-PROD"));
+    }
+}
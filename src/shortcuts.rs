@@ -0,0 +1,61 @@
+//! Keyboard shortcuts, which only work while no text field has focus
+
+use iced::{
+    event,
+    keyboard::{self, KeyCode},
+    Event,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shortcut {
+    /// Highlights the next object, with j or the down arrow
+    Next,
+    /// Highlights the previous object, with k or the up arrow
+    Previous,
+    /// Opens the YAML of the highlighted object, with enter
+    Open,
+    /// Opens the logs of the highlighted pod, with l
+    Logs,
+    /// Asks to delete the highlighted object, with the delete key
+    Delete,
+    /// Focuses the filter, with /
+    Filter,
+    /// Opens the command palette, with :
+    CommandPalette,
+    /// Closes whatever is open, with escape
+    Escape,
+}
+
+/// # Returns
+/// The shortcut that the event triggers, if any
+pub fn from_event(event: &Event, status: event::Status) -> Option<Shortcut> {
+    match event {
+        // escape works from text fields too, so that the command palette can be closed while typing
+        Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: KeyCode::Escape,
+            ..
+        }) => Some(Shortcut::Escape),
+        // the key was typed into a text field
+        _ if status == event::Status::Captured => None,
+        Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code,
+            modifiers,
+        }) if modifiers.is_empty() => match key_code {
+            KeyCode::Down => Some(Shortcut::Next),
+            KeyCode::Up => Some(Shortcut::Previous),
+            KeyCode::Enter | KeyCode::NumpadEnter => Some(Shortcut::Open),
+            KeyCode::Delete => Some(Shortcut::Delete),
+            _ => None,
+        },
+        // matched by what was typed rather than by key, so that they work with every keyboard layout
+        Event::Keyboard(keyboard::Event::CharacterReceived(character)) => match character {
+            'j' => Some(Shortcut::Next),
+            'k' => Some(Shortcut::Previous),
+            'l' => Some(Shortcut::Logs),
+            '/' => Some(Shortcut::Filter),
+            ':' => Some(Shortcut::CommandPalette),
+            _ => None,
+        },
+        _ => None,
+    }
+}
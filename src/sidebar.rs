@@ -0,0 +1,120 @@
+use iced::{
+    widget::{button, column, container, horizontal_space, row, text, vertical_space, Column},
+    Alignment, Element, Length, Padding,
+};
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    circular_loading_theme::{as_circular_theme, CircularLoadingTheme},
+    colours::Palette,
+    connection::{Connection, ConnectionId, Health},
+    container_theme::{as_container_theme, ContainerTheme},
+    custom_widgets::circular_loading_spinner::Circular,
+    messages::Message,
+    sizes,
+};
+
+const SIDEBAR_WIDTH: f32 = 240.0;
+
+/// Lists the connected contexts, along with whether their cluster can be reached.
+/// Clicking on a context switches to it.
+pub fn view(
+    connections: &[Connection],
+    active_connection: Option<ConnectionId>,
+    palette: Palette,
+) -> Element<'_, Message> {
+    let entries: Vec<Element<Message>> = connections
+        .iter()
+        .map(|connection| {
+            entry(
+                connection,
+                active_connection == Some(connection.get_id()),
+                palette,
+            )
+        })
+        .collect();
+
+    container(
+        column![
+            text("Contexts")
+                .size(sizes::P)
+                .style(palette.secondary_text),
+            Column::with_children(entries).spacing(sizes::SMOL_SEP),
+            button(container(text("Add Context")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }))
+            .on_press(Message::ChangeContextRequested)
+            .style(as_button_theme(ButtonTheme::Secondary)),
+            vertical_space(Length::Fill),
+            button(container(text("History")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }))
+            .on_press(Message::HistoryRequested)
+            .style(as_button_theme(ButtonTheme::Ghost)),
+            button(container(text("Debug Log")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }))
+            .on_press(Message::DebugLogRequested)
+            .style(as_button_theme(ButtonTheme::Ghost)),
+            button(container(text("Settings")).padding(Padding {
+                bottom: 0.0,
+                top: 0.0,
+                left: sizes::SEP,
+                right: sizes::SEP,
+            }))
+            .on_press(Message::SettingsRequested)
+            .style(as_button_theme(ButtonTheme::Secondary)),
+        ]
+        .spacing(sizes::SEP),
+    )
+    .width(SIDEBAR_WIDTH)
+    .height(Length::Fill)
+    .padding(sizes::SEP)
+    .style(as_container_theme(ContainerTheme::Dark))
+    .into()
+}
+
+fn entry(connection: &Connection, active: bool, palette: Palette) -> Element<'_, Message> {
+    let health: Element<Message> = match connection.get_health() {
+        // the connection asks the user to log in again, so point them to it
+        _ if connection.is_auth_expired() => text("!").size(sizes::P).style(palette.danger).into(),
+        Health::Unknown => Circular::new()
+            .size(sizes::P)
+            .bar_height(2.0)
+            .style(as_circular_theme(CircularLoadingTheme::Secondary))
+            .into(),
+        Health::Reachable => text("●").size(sizes::P).style(palette.success).into(),
+        Health::Unreachable => text("●").size(sizes::P).style(palette.danger).into(),
+    };
+
+    row![
+        button(
+            row![health, text(connection.get_context().get_name())]
+                .spacing(sizes::SEP)
+                .align_items(Alignment::Center)
+        )
+        .width(Length::Fill)
+        .on_press(Message::ConnectionSelected(connection.get_id()))
+        .style(as_button_theme(if active {
+            ButtonTheme::Secondary
+        } else {
+            ButtonTheme::Ghost
+        })),
+        horizontal_space(sizes::SMOL_SEP),
+        button(text("×").size(sizes::P))
+            .on_press(Message::DisconnectRequested(connection.get_id()))
+            .padding(sizes::SMOL_SEP)
+            .style(as_button_theme(ButtonTheme::Ghost)),
+    ]
+    .align_items(Alignment::Center)
+    .into()
+}
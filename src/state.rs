@@ -0,0 +1,236 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error, kube_context::Impersonation, resource_type::ResourceType, settings::UserSettings,
+};
+
+pub const STATE_DIR: &str = "iced-k8s";
+const STATE_FILE: &str = "state.json";
+
+/// What the app remembers between launches
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppState {
+    /// Name of the context that was being viewed when the app was closed
+    pub last_context: Option<String>,
+    /// The namespace that was last picked, by context name
+    pub namespaces: HashMap<String, String>,
+    /// Namespaces that can be picked with a single click, by context name
+    pub favourite_namespaces: HashMap<String, Vec<String>>,
+    /// The tabs that were open, by context name
+    pub open_tabs: HashMap<String, Vec<SavedTab>>,
+    pub window: Option<WindowGeometry>,
+    pub settings: UserSettings,
+    /// Kubeconfig files that the user added, which are read after the ones in `$KUBECONFIG`
+    pub extra_kubeconfigs: Vec<PathBuf>,
+    /// When the cluster of each context was first found to be unreachable, by context name.
+    /// Contexts are removed from here as soon as their cluster can be reached again.
+    pub unreachable_since: HashMap<String, DateTime<Utc>>,
+    /// Who requests are sent on behalf of, by context name
+    pub impersonations: HashMap<String, Impersonation>,
+    /// Names of the contexts that nothing can be changed in
+    pub read_only_contexts: Vec<String>,
+}
+
+impl AppState {
+    pub fn is_favourite_namespace(&self, context_name: &str, namespace: &str) -> bool {
+        self.favourite_namespaces
+            .get(context_name)
+            .is_some_and(|favourites| favourites.iter().any(|favourite| favourite == namespace))
+    }
+
+    pub fn get_favourite_namespaces(&self, context_name: &str) -> &[String] {
+        self.favourite_namespaces
+            .get(context_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Remembers whether the cluster of the context could be reached
+    ///
+    /// # Returns
+    /// Whether anything changed
+    pub fn record_probe(&mut self, context_name: &str, reachable: bool) -> bool {
+        if reachable {
+            self.unreachable_since.remove(context_name).is_some()
+        } else if self.unreachable_since.contains_key(context_name) {
+            false
+        } else {
+            self.unreachable_since
+                .insert(context_name.to_owned(), Utc::now());
+            true
+        }
+    }
+
+    pub fn is_read_only(&self, context_name: &str) -> bool {
+        self.read_only_contexts
+            .iter()
+            .any(|read_only| read_only == context_name)
+    }
+
+    pub fn set_read_only(&mut self, context_name: String, read_only: bool) {
+        self.read_only_contexts
+            .retain(|existing| *existing != context_name);
+        if read_only {
+            self.read_only_contexts.push(context_name);
+        }
+    }
+
+    /// Moves everything that's remembered about the context called `from` to its new name, `to`
+    pub fn rename_context(&mut self, from: &str, to: &str) {
+        fn rename<V>(by_context: &mut HashMap<String, V>, from: &str, to: &str) {
+            if let Some(value) = by_context.remove(from) {
+                by_context.insert(to.to_owned(), value);
+            }
+        }
+        rename(&mut self.namespaces, from, to);
+        rename(&mut self.favourite_namespaces, from, to);
+        rename(&mut self.open_tabs, from, to);
+        rename(&mut self.unreachable_since, from, to);
+        rename(&mut self.impersonations, from, to);
+        for read_only in self.read_only_contexts.iter_mut() {
+            if read_only == from {
+                *read_only = to.to_owned();
+            }
+        }
+        if self.last_context.as_deref() == Some(from) {
+            self.last_context = Some(to.to_owned());
+        }
+    }
+
+    /// Adds the namespace to the favourites of the context, or removes it if it's already a favourite
+    pub fn toggle_favourite_namespace(&mut self, context_name: String, namespace: String) {
+        let favourites = self.favourite_namespaces.entry(context_name).or_default();
+        match favourites
+            .iter()
+            .position(|favourite| *favourite == namespace)
+        {
+            Some(index) => {
+                favourites.remove(index);
+            }
+            None => favourites.push(namespace),
+        }
+    }
+}
+
+/// A tab that should be reopened the next time the context is connected to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SavedTab {
+    Namespace {
+        namespace: String,
+        #[serde(default)]
+        all_namespaces: bool,
+        #[serde(default)]
+        resource_type: Option<ResourceType>,
+    },
+    PodLogs {
+        namespace: String,
+        pod_name: String,
+    },
+    YamlEditor {
+        namespace: String,
+        resource_type: ResourceType,
+        name: String,
+    },
+    Permissions {
+        namespace: String,
+        #[serde(default)]
+        all_namespaces: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+}
+
+fn state_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| config_dir.join(STATE_DIR).join(STATE_FILE))
+}
+
+/// Reads the state that was saved the last time the app was used.
+///
+/// # Returns
+/// The saved state, or the default state if nothing was saved or the state file couldn't be read
+pub fn load() -> AppState {
+    let Some(path) = state_file() else {
+        return AppState::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(migrate)
+            .and_then(serde_json::from_value)
+            .unwrap_or_else(|error| {
+                tracing::warn!("Ignoring invalid state file {}: {}", path.display(), error);
+                AppState::default()
+            }),
+        Err(_) => AppState::default(),
+    }
+}
+
+/// Moves the refresh interval into the settings, since it was kept at the top level before there were settings
+fn migrate(mut state: serde_json::Value) -> serde_json::Value {
+    let Some(object) = state.as_object_mut() else {
+        return state;
+    };
+    if let Some(refresh_interval) = object.remove("refresh_interval") {
+        let settings = object
+            .entry("settings")
+            .or_insert_with(|| serde_json::json!({}));
+        if let Some(settings) = settings.as_object_mut() {
+            settings
+                .entry("refresh_interval")
+                .or_insert(refresh_interval);
+        }
+    }
+    state
+}
+
+/// Writes the given state to the state file in the user's config directory.
+///
+/// This is done synchronously, since the file is small and it avoids two saves racing each other.
+pub fn save(state: &AppState) -> Result<(), Error> {
+    let Some(path) = state_file() else {
+        return Err(Error::new("Unable to find the config directory".into()));
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // write to a temporary file first so that a crash doesn't leave behind a half written file
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_string_pretty(state)?)?;
+    fs::rename(temp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renaming_a_context_keeps_what_was_remembered_about_it() {
+        let mut state = AppState {
+            last_context: Some(String::from("old")),
+            namespaces: HashMap::from([(String::from("old"), String::from("web"))]),
+            favourite_namespaces: HashMap::from([(String::from("old"), vec![String::from("web")])]),
+            unreachable_since: HashMap::from([(String::from("other"), Utc::now())]),
+            read_only_contexts: vec![String::from("old"), String::from("other")],
+            ..AppState::default()
+        };
+
+        state.rename_context("old", "new");
+
+        assert_eq!(state.last_context.as_deref(), Some("new"));
+        assert_eq!(state.namespaces.get("new").map(String::as_str), Some("web"));
+        assert!(!state.namespaces.contains_key("old"));
+        assert!(state.is_favourite_namespace("new", "web"));
+        assert!(state.unreachable_since.contains_key("other"));
+        assert_eq!(state.read_only_contexts, vec!["new", "other"]);
+    }
+}
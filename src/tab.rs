@@ -0,0 +1,192 @@
+use iced::{Command, Element};
+
+use crate::{
+    cluster::Cluster,
+    cluster_object::ClusterObject,
+    kube_context::KubeContext,
+    messages::{
+        ClusterMessage, Message, PermissionExplorerMessage, PodLogsMessage, YamlEditorMessage,
+    },
+    permission_explorer::PermissionExplorer,
+    pod_logs::PodLogs,
+    resource_type::ResourceType,
+    state::{AppState, SavedTab},
+    yaml_editor::YamlEditor,
+};
+
+/// Identifies a tab, so that messages from background work end up at the right tab
+pub type TabId = usize;
+
+/// Describes a tab that should be opened
+#[derive(Debug, Clone)]
+pub enum TabRequest {
+    /// Lists the workloads in the context's namespace, optionally only those of one resource type
+    Namespace(KubeContext, Option<ResourceType>),
+    PodLogs(KubeContext, String),
+    YamlEditor(KubeContext, ClusterObject),
+    /// Shows what the user may do in the context's namespace
+    Permissions(KubeContext),
+}
+
+impl TabRequest {
+    /// Describes the saved tab, opened for the given context
+    pub fn from_saved(context: &KubeContext, saved_tab: SavedTab) -> TabRequest {
+        match saved_tab {
+            SavedTab::Namespace {
+                namespace,
+                all_namespaces,
+                resource_type,
+            } => {
+                let context = context.with_namespace(namespace);
+                let context = if all_namespaces {
+                    context.with_all_namespaces()
+                } else {
+                    context
+                };
+                TabRequest::Namespace(context, resource_type)
+            }
+            SavedTab::PodLogs {
+                namespace,
+                pod_name,
+            } => TabRequest::PodLogs(context.with_namespace(namespace), pod_name),
+            SavedTab::YamlEditor {
+                namespace,
+                resource_type,
+                name,
+            } => TabRequest::YamlEditor(
+                context.with_namespace(namespace.clone()),
+                ClusterObject::new(name, namespace, resource_type, None),
+            ),
+            SavedTab::Permissions {
+                namespace,
+                all_namespaces,
+            } => {
+                let context = context.with_namespace(namespace);
+                TabRequest::Permissions(if all_namespaces {
+                    context.with_all_namespaces()
+                } else {
+                    context
+                })
+            }
+        }
+    }
+
+    pub fn get_context(&self) -> &KubeContext {
+        match self {
+            TabRequest::Namespace(context, _) => context,
+            TabRequest::PodLogs(context, _) => context,
+            TabRequest::YamlEditor(context, _) => context,
+            TabRequest::Permissions(context) => context,
+        }
+    }
+}
+
+/// An independent view that's open in the tab bar of a connection
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Tab {
+    Cluster(Cluster),
+    PodLogs(PodLogs),
+    YamlEditor(YamlEditor),
+    PermissionExplorer(PermissionExplorer),
+}
+
+impl Tab {
+    pub fn new(id: TabId, request: TabRequest) -> Tab {
+        match request {
+            TabRequest::Namespace(context, resource_type) => {
+                Tab::Cluster(Cluster::new(id, context, resource_type, None))
+            }
+            TabRequest::PodLogs(context, pod_name) => {
+                Tab::PodLogs(PodLogs::new(id, context, pod_name))
+            }
+            TabRequest::YamlEditor(context, cluster_object) => {
+                Tab::YamlEditor(YamlEditor::new(id, context, cluster_object))
+            }
+            TabRequest::Permissions(context) => {
+                Tab::PermissionExplorer(PermissionExplorer::new(id, context))
+            }
+        }
+    }
+
+    pub fn get_id(&self) -> TabId {
+        match self {
+            Tab::Cluster(cluster) => cluster.get_id(),
+            Tab::PodLogs(pod_logs) => pod_logs.get_id(),
+            Tab::YamlEditor(yaml_editor) => yaml_editor.get_id(),
+            Tab::PermissionExplorer(permission_explorer) => permission_explorer.get_id(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Tab::Cluster(cluster) => match cluster.get_resource_type() {
+                Some(resource_type) => format!(
+                    "{} ({}s)",
+                    cluster.get_context().get_namespace_label(),
+                    resource_type
+                ),
+                None => cluster.get_context().get_namespace_label(),
+            },
+            Tab::PodLogs(pod_logs) => format!("Logs: {}", pod_logs.get_pod_name()),
+            Tab::YamlEditor(yaml_editor) => {
+                format!("YAML: {}", yaml_editor.get_cluster_object().name)
+            }
+            Tab::PermissionExplorer(permission_explorer) => format!(
+                "Permissions: {}",
+                permission_explorer.get_context().get_namespace_label()
+            ),
+        }
+    }
+
+    /// Describes the tab so that it can be reopened the next time the app is launched
+    pub fn to_saved(&self) -> SavedTab {
+        match self {
+            Tab::Cluster(cluster) => SavedTab::Namespace {
+                namespace: cluster.get_context().get_namespace(),
+                all_namespaces: cluster.get_context().is_all_namespaces(),
+                resource_type: cluster.get_resource_type(),
+            },
+            Tab::PodLogs(pod_logs) => SavedTab::PodLogs {
+                namespace: pod_logs.get_context().get_namespace(),
+                pod_name: pod_logs.get_pod_name().to_owned(),
+            },
+            Tab::YamlEditor(yaml_editor) => SavedTab::YamlEditor {
+                namespace: yaml_editor.get_context().get_namespace(),
+                resource_type: yaml_editor.get_cluster_object().r#type,
+                name: yaml_editor.get_cluster_object().name.clone(),
+            },
+            Tab::PermissionExplorer(permission_explorer) => SavedTab::Permissions {
+                namespace: permission_explorer.get_context().get_namespace(),
+                all_namespaces: permission_explorer.get_context().is_all_namespaces(),
+            },
+        }
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        state: &'a AppState,
+        unread_notifications: usize,
+    ) -> Element<'a, Message> {
+        let palette = state.settings.theme.palette();
+        match self {
+            Tab::Cluster(cluster) => cluster.view(state, unread_notifications),
+            Tab::PodLogs(pod_logs) => pod_logs.view(palette),
+            Tab::YamlEditor(yaml_editor) => yaml_editor.view(palette),
+            Tab::PermissionExplorer(permission_explorer) => permission_explorer.view(palette),
+        }
+    }
+
+    /// Fetches the latest state of whatever the tab is showing,
+    /// cancelling the refresh that's in flight
+    pub fn reload(&mut self) -> Command<Message> {
+        match self {
+            Tab::Cluster(cluster) => cluster.update(ClusterMessage::ReloadRequested),
+            Tab::PodLogs(pod_logs) => pod_logs.update(PodLogsMessage::ReloadRequested),
+            Tab::YamlEditor(yaml_editor) => yaml_editor.update(YamlEditorMessage::ReloadRequested),
+            Tab::PermissionExplorer(permission_explorer) => {
+                permission_explorer.update(PermissionExplorerMessage::ReloadRequested)
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Asks for the name of a protected context to be typed before a delete in it is undone
+
+use iced::{
+    widget::{button, container, horizontal_space, row, text, text_input},
+    Alignment, Element, Length, Padding,
+};
+use kube::api::DynamicObject;
+
+use crate::{
+    button_theme::{as_button_theme, ButtonTheme},
+    cluster_object::ClusterObject,
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    kube_context::KubeContext,
+    messages::Message,
+    sizes,
+};
+
+#[derive(Debug, Clone)]
+pub struct UndoConfirmation {
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    snapshot: DynamicObject,
+    field_value: String,
+}
+
+impl UndoConfirmation {
+    pub fn new(
+        context: KubeContext,
+        cluster_object: ClusterObject,
+        snapshot: DynamicObject,
+    ) -> UndoConfirmation {
+        UndoConfirmation {
+            context,
+            cluster_object,
+            snapshot,
+            field_value: String::from(""),
+        }
+    }
+
+    pub fn set_field_value(&mut self, value: String) {
+        self.field_value = value;
+    }
+
+    /// Whether the name of the context was typed correctly
+    pub fn is_confirmed(&self) -> bool {
+        self.context.is_confirmed(&self.field_value)
+    }
+
+    /// # Returns
+    /// What to recreate
+    pub fn into_parts(self) -> (KubeContext, ClusterObject, DynamicObject) {
+        (self.context, self.cluster_object, self.snapshot)
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let mut confirm_button = button(container(text("Create")).padding(Padding {
+            bottom: 0.0,
+            top: 0.0,
+            left: sizes::SEP,
+            right: sizes::SEP,
+        }))
+        .style(iced::theme::Button::Destructive);
+        if self.is_confirmed() {
+            confirm_button = confirm_button.on_press(Message::RecreateConfirmed);
+        }
+
+        container(
+            row![
+                text(format!(
+                    "Are you sure you want to create {} {} again in {}?",
+                    self.cluster_object.r#type,
+                    self.cluster_object.name,
+                    self.context.get_name()
+                ))
+                .style(palette.text),
+                horizontal_space(Length::Fill),
+                text_input(
+                    &format!("Type {} to confirm", self.context.get_name()),
+                    &self.field_value,
+                )
+                .on_input(Message::RecreateConfirmationFieldChanged)
+                .on_submit(Message::RecreateConfirmed)
+                .width(300),
+                button(container(text("Cancel")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(Message::RecreateCancelled)
+                .style(as_button_theme(ButtonTheme::Secondary)),
+                confirm_button,
+            ]
+            .spacing(sizes::SEP)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding(sizes::SEP)
+        .style(as_container_theme(ContainerTheme::Light))
+        .into()
+    }
+}
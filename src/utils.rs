@@ -1,4 +1,2 @@
 /// Returns a future that immediately resolves
-pub async fn resolved() {
-    ()
-}
+pub async fn resolved() {}
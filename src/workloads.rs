@@ -1,26 +1,114 @@
-use crate::cluster_object::ClusterObject;
+use std::collections::HashSet;
+
+use crate::cluster_object::{ClusterObject, ObjectKey};
+use crate::container_theme::{as_container_theme, ContainerTheme};
+use crate::kube_context::Safety;
+use crate::permissions::Rules;
+use crate::resource_type::ResourceType;
+use crate::settings::UserSettings;
+use crate::tab::TabId;
 
 use crate::sizes;
 use crate::Message;
-use iced::widget::column;
+use iced::widget::{column, container, row, text};
 use iced::Element;
 
 #[derive(Debug, Clone, Default)]
 pub struct Workloads {
     cluster_objects: Vec<ClusterObject>,
+    /// The resource types that the user isn't permitted to list
+    forbidden: Vec<ResourceType>,
+    /// What the user may do in the namespace, if it's known
+    rules: Option<Rules>,
 }
 
 impl Workloads {
-    pub fn new(cluster_objects: Vec<ClusterObject>) -> Workloads {
-        Workloads { cluster_objects }
+    pub fn new(
+        cluster_objects: Vec<ClusterObject>,
+        forbidden: Vec<ResourceType>,
+        rules: Option<Rules>,
+    ) -> Workloads {
+        Workloads {
+            cluster_objects,
+            forbidden,
+            rules,
+        }
+    }
+
+    pub fn get_cluster_objects(&self) -> &[ClusterObject] {
+        &self.cluster_objects
+    }
+
+    pub fn get_rules(&self) -> Option<&Rules> {
+        self.rules.as_ref()
     }
 
-    pub fn view(&self) -> Element<Message> {
-        let workload_elts: Vec<Element<Message>> = self
-            .cluster_objects
+    /// Whether the user isn't permitted to list any of the types that were asked for,
+    /// where `resource_type` is the only type that was asked for, or `None` for every type
+    pub fn is_all_forbidden(&self, resource_type: Option<ResourceType>) -> bool {
+        match resource_type {
+            Some(resource_type) => self.forbidden.contains(&resource_type),
+            None => self.forbidden.len() == ResourceType::ALL.len(),
+        }
+    }
+
+    /// # Returns
+    /// The objects that match the filter, in the order they're listed
+    pub fn matching<'a>(&'a self, filter: &'a str) -> impl Iterator<Item = &'a ClusterObject> {
+        self.cluster_objects
             .iter()
-            .map(|cluster_object| cluster_object.view())
+            .filter(move |cluster_object| cluster_object.matches(filter))
+    }
+
+    /// Only the objects that match `filter` are listed, and the ones in `selection` are checked
+    /// `highlighted` is the object that keyboard shortcuts act on
+    #[allow(clippy::too_many_arguments)]
+    pub fn view<'a>(
+        &'a self,
+        tab_id: TabId,
+        settings: &UserSettings,
+        show_namespace: bool,
+        safety: Safety,
+        filter: &'a str,
+        selection: &HashSet<ObjectKey>,
+        highlighted: Option<&ObjectKey>,
+    ) -> Element<'a, Message> {
+        let palette = settings.theme.palette();
+        let mut workload_elts: Vec<Element<Message>> = self
+            .matching(filter)
+            .map(|cluster_object| {
+                let key = cluster_object.key();
+                let view = cluster_object.view(
+                    tab_id,
+                    settings,
+                    show_namespace,
+                    self.rules.as_ref(),
+                    safety,
+                    selection.contains(&key),
+                );
+                if highlighted == Some(&key) {
+                    container(view)
+                        .style(as_container_theme(ContainerTheme::Highlighted))
+                        .into()
+                } else {
+                    view
+                }
+            })
             .collect();
+        // the rest of the types are still shown, so only say which ones are missing
+        workload_elts.extend(self.forbidden.iter().map(|r#type| {
+            row![
+                text(format!("{}s", r#type))
+                    .size(sizes::P)
+                    .width(400)
+                    .style(palette.text),
+                text("Not permitted: you don't have permission to list these here")
+                    .size(sizes::P)
+                    .style(palette.secondary_text),
+            ]
+            .spacing(sizes::P * 2.0)
+            .into()
+        }));
 
         column(workload_elts).spacing(sizes::SEP).into()
     }
@@ -0,0 +1,324 @@
+use iced::{
+    widget::{
+        button, column, container, horizontal_space, row, scrollable, text, text_input,
+        vertical_rule, Column,
+    },
+    Alignment, Command, Element, Font, Length, Padding,
+};
+
+use crate::{
+    audit::{Action, AuditEntry},
+    button_theme::{as_button_theme, ButtonTheme},
+    cluster_object::ClusterObject,
+    colours::Palette,
+    container_theme::{as_container_theme, ContainerTheme},
+    custom_widgets::toast::{Status, Toast},
+    error::Error,
+    kube_context::{KubeContext, Safety},
+    kube_interface,
+    messages::{Message, YamlEditorMessage},
+    sizes,
+    tab::TabId,
+    utils,
+};
+
+/// Shows the YAML of an object in the cluster, and lets the user edit it and replace the object in the cluster.
+///
+/// The YAML is edited line by line, since iced doesn't have a multi-line text input.
+/// Pressing enter in a line inserts a new line below it, and the button next to a line removes it.
+#[derive(Debug, Clone)]
+pub struct YamlEditor {
+    id: TabId,
+    context: KubeContext,
+    cluster_object: ClusterObject,
+    lines: Option<Vec<String>>,
+    /// Whether the user edited the YAML since it was last loaded
+    dirty: bool,
+    /// Whether the last load failed, so that a failure is reported once rather than on every refresh
+    load_failed: bool,
+    /// The context name that was typed to confirm applying the changes, in a protected context
+    confirmation_field_value: String,
+}
+
+impl YamlEditor {
+    pub fn new(id: TabId, context: KubeContext, cluster_object: ClusterObject) -> YamlEditor {
+        YamlEditor {
+            id,
+            context,
+            cluster_object,
+            lines: None,
+            dirty: false,
+            load_failed: false,
+            confirmation_field_value: String::from(""),
+        }
+    }
+
+    pub fn get_id(&self) -> TabId {
+        self.id
+    }
+
+    pub fn get_context(&self) -> &KubeContext {
+        &self.context
+    }
+
+    pub fn get_cluster_object(&self) -> &ClusterObject {
+        &self.cluster_object
+    }
+
+    fn message(&self, message: YamlEditorMessage) -> Message {
+        Message::YamlEditorMessage(self.id, message)
+    }
+
+    pub fn view(&self, palette: Palette) -> Element<'_, Message> {
+        let mut apply_button = button(container(text("Apply")).padding(Padding {
+            bottom: 0.0,
+            top: 0.0,
+            left: sizes::SEP,
+            right: sizes::SEP,
+        }))
+        .style(as_button_theme(ButtonTheme::Secondary));
+        if self.can_apply() {
+            apply_button = apply_button.on_press(self.message(YamlEditorMessage::ApplyRequested));
+        }
+
+        let mut header_row = row![
+            text(format!(
+                "{} {}",
+                self.cluster_object.r#type, self.cluster_object.name
+            ))
+            .size(sizes::H2)
+            .style(palette.on_accent),
+            vertical_rule(sizes::P),
+            self.context.view(palette),
+            horizontal_space(Length::Fill),
+        ]
+        .spacing(sizes::SEP)
+        .align_items(Alignment::Center);
+        if self.dirty && self.context.get_safety() == Safety::Protected {
+            header_row = header_row.push(
+                text_input(
+                    &format!("Type {} to confirm", self.context.get_name()),
+                    &self.confirmation_field_value,
+                )
+                .on_input(|value| self.message(YamlEditorMessage::ConfirmationFieldChanged(value)))
+                .width(300),
+            );
+        }
+        header_row = header_row
+            .push(
+                button(container(text("Revert")).padding(Padding {
+                    bottom: 0.0,
+                    top: 0.0,
+                    left: sizes::SEP,
+                    right: sizes::SEP,
+                }))
+                .on_press(self.message(YamlEditorMessage::RevertRequested))
+                .style(as_button_theme(ButtonTheme::Secondary)),
+            )
+            .push(apply_button);
+
+        let header = container(header_row)
+            .width(Length::Fill)
+            .padding(Padding {
+                bottom: sizes::SEP,
+                top: sizes::SEP,
+                left: 2.0 * sizes::SEP,
+                right: 2.0 * sizes::SEP,
+            })
+            .style(as_container_theme(ContainerTheme::Dark));
+
+        let editor: Element<Message> = match &self.lines {
+            Some(lines) => scrollable(Column::with_children(
+                lines
+                    .iter()
+                    .enumerate()
+                    .map(|(index, line)| {
+                        row![
+                            text_input("", line)
+                                .on_input(move |value| {
+                                    Message::YamlEditorMessage(
+                                        self.id,
+                                        YamlEditorMessage::LineChanged(index, value),
+                                    )
+                                })
+                                .on_submit(self.message(YamlEditorMessage::NewLineRequested(index)))
+                                .font(Font::MONOSPACE)
+                                .size(sizes::P)
+                                .padding(0),
+                            button(text("Remove").size(sizes::P))
+                                .padding(0)
+                                .on_press(self.message(YamlEditorMessage::LineRemoved(index)))
+                                .style(as_button_theme(ButtonTheme::Ghost)),
+                        ]
+                        .spacing(sizes::SEP)
+                        .align_items(Alignment::Center)
+                        .into()
+                    })
+                    .collect(),
+            ))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into(),
+            None if self.load_failed => text("Unable to load the YAML for this resource")
+                .style(palette.danger)
+                .into(),
+            None => text("Loading YAML...").style(palette.text).into(),
+        };
+
+        column![
+            header,
+            container(editor)
+                .padding(sizes::SEP)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(as_container_theme(ContainerTheme::Light))
+        ]
+        .into()
+    }
+
+    fn audit_entry(&self, result: Result<(), &Error>) -> AuditEntry {
+        AuditEntry::new(
+            &self.context,
+            self.cluster_object.namespace.clone(),
+            Action::Apply,
+            self.cluster_object.r#type,
+            self.cluster_object.name.clone(),
+            result,
+        )
+    }
+
+    /// Whether the changes can be applied, given what the context allows
+    fn can_apply(&self) -> bool {
+        self.dirty
+            && match self.context.get_safety() {
+                Safety::Normal => true,
+                Safety::Protected => self.context.is_confirmed(&self.confirmation_field_value),
+                Safety::ReadOnly => false,
+            }
+    }
+
+    pub fn update(&mut self, message: YamlEditorMessage) -> Command<Message> {
+        let id = self.id;
+        match message {
+            YamlEditorMessage::ReloadRequested => {
+                // don't throw away the user's changes
+                if self.dirty {
+                    return Command::none();
+                }
+
+                Command::perform(
+                    kube_interface::fetch_yaml(self.context.clone(), self.cluster_object.clone()),
+                    move |res| Message::YamlEditorMessage(id, YamlEditorMessage::YamlLoaded(res)),
+                )
+            }
+            YamlEditorMessage::RevertRequested => {
+                self.dirty = false;
+
+                self.update(YamlEditorMessage::ReloadRequested)
+            }
+            YamlEditorMessage::YamlLoaded(Ok(yaml)) => {
+                self.load_failed = false;
+                if !self.dirty {
+                    self.lines = Some(yaml.lines().map(String::from).collect());
+                }
+
+                Command::none()
+            }
+            YamlEditorMessage::YamlLoaded(Err(error)) if error.is_auth_expired() => {
+                let context_name = self.context.get_name();
+                Command::perform(utils::resolved(), move |_ignored| {
+                    Message::AuthExpired(context_name)
+                })
+            }
+            // the refresh keeps failing the same way, e.g. once the object was deleted
+            YamlEditorMessage::YamlLoaded(Err(..)) if self.load_failed => Command::none(),
+            YamlEditorMessage::YamlLoaded(Err(error)) => {
+                self.load_failed = true;
+                tracing::warn!("Unable to load YAML: {}", error.get_message());
+                let toast = error.to_toast("Unable to load the YAML for this resource.");
+
+                Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast))
+            }
+            YamlEditorMessage::LineChanged(index, value) => {
+                if let Some(line) = self.lines.as_mut().and_then(|lines| lines.get_mut(index)) {
+                    *line = value;
+                    self.dirty = true;
+                }
+
+                Command::none()
+            }
+            YamlEditorMessage::NewLineRequested(index) => {
+                if let Some(lines) = self.lines.as_mut() {
+                    // keep the indentation of the line above, since that's usually what's wanted in YAML
+                    if let Some(line) = lines.get(index) {
+                        let indentation: String = line
+                            .chars()
+                            .take_while(|character| character.is_whitespace())
+                            .collect();
+                        lines.insert(index + 1, indentation);
+                        self.dirty = true;
+                    }
+                }
+
+                Command::none()
+            }
+            YamlEditorMessage::LineRemoved(index) => {
+                if let Some(lines) = self.lines.as_mut().filter(|lines| index < lines.len()) {
+                    lines.remove(index);
+                    self.dirty = true;
+                }
+
+                Command::none()
+            }
+            YamlEditorMessage::ConfirmationFieldChanged(value) => {
+                self.confirmation_field_value = value;
+
+                Command::none()
+            }
+            YamlEditorMessage::ApplyRequested if !self.can_apply() => Command::none(),
+            YamlEditorMessage::ApplyRequested => match &self.lines {
+                Some(lines) => Command::perform(
+                    kube_interface::replace_yaml(
+                        self.context.clone(),
+                        self.cluster_object.clone(),
+                        lines.join("\n"),
+                    ),
+                    move |res| Message::YamlEditorMessage(id, YamlEditorMessage::Applied(res)),
+                ),
+                None => Command::none(),
+            },
+            YamlEditorMessage::Applied(Ok(())) => {
+                self.dirty = false;
+                self.confirmation_field_value = String::from("");
+                let name = self.cluster_object.name.clone();
+                let entry = self.audit_entry(Ok(()));
+
+                Command::batch(vec![
+                    self.update(YamlEditorMessage::ReloadRequested),
+                    Command::perform(utils::resolved(), move |_ignored| {
+                        Message::ActionPerformed(entry)
+                    }),
+                    Command::perform(utils::resolved(), move |_ignored| {
+                        Message::AddToast(Toast::new(
+                            Status::Success,
+                            "Applied",
+                            format!("The changes to {} were applied", name),
+                        ))
+                    }),
+                ])
+            }
+            YamlEditorMessage::Applied(Err(error)) => {
+                tracing::warn!("Unable to apply YAML: {}", error.get_message());
+                let toast = error.to_toast("Unable to apply the changes.");
+                let entry = self.audit_entry(Err(&error));
+
+                Command::batch(vec![
+                    Command::perform(utils::resolved(), move |_ignored| {
+                        Message::ActionPerformed(entry)
+                    }),
+                    Command::perform(utils::resolved(), move |_ignored| Message::AddToast(toast)),
+                ])
+            }
+        }
+    }
+}